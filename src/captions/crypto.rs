@@ -0,0 +1,311 @@
+use std::{
+    fmt, fs,
+    io::{Read, Write},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::{
+    XChaCha20Poly1305, XNonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{index::SUMMARY_SUFFIX, storage::SessionStore};
+use crate::utils::hex;
+
+const ENVELOPE_VERSION: u8 = 1;
+const ENVELOPE_ALGORITHM: &str = "xchacha20poly1305";
+const KEY_LEN: usize = 32;
+
+/// On-disk wrapper for an encrypted session document. Every write uses a fresh
+/// data key which is itself sealed with the configured master key.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    hammock_envelope: u8,
+    alg: String,
+    key_id: String,
+    wrapped_key: String,
+    key_nonce: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+struct MasterKey {
+    id: String,
+    cipher: XChaCha20Poly1305,
+}
+
+impl MasterKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != KEY_LEN {
+            bail!(
+                "caption encryption keys must be {KEY_LEN} bytes, got {}",
+                bytes.len()
+            );
+        }
+        let digest = Sha256::digest(bytes);
+        let id = hex(&digest[..4]);
+        let cipher = XChaCha20Poly1305::new_from_slice(bytes)
+            .map_err(|_| anyhow!("invalid caption encryption key"))?;
+        Ok(Self { id, cipher })
+    }
+
+    fn parse(raw: &str) -> Result<Self> {
+        let trimmed = raw.trim();
+        let bytes =
+            if trimmed.len() == KEY_LEN * 2 && trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
+                (0..trimmed.len())
+                    .step_by(2)
+                    .map(|idx| u8::from_str_radix(&trimmed[idx..idx + 2], 16))
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("decoding hex caption encryption key")?
+            } else {
+                BASE64
+                    .decode(trimmed)
+                    .context("decoding base64 caption encryption key")?
+            };
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Current master key plus any retired keys that can still decrypt older files.
+pub struct CaptionKeyring {
+    current: MasterKey,
+    previous: Vec<MasterKey>,
+}
+
+impl fmt::Debug for CaptionKeyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptionKeyring")
+            .field("current", &self.current.id)
+            .field(
+                "previous",
+                &self.previous.iter().map(|key| &key.id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl CaptionKeyring {
+    /// Builds the keyring from `CAPTION_ENCRYPTION_KEY` / `CAPTION_ENCRYPTION_KEY_FILE`
+    /// and the optional comma-separated `CAPTION_ENCRYPTION_PREVIOUS_KEYS`.
+    pub fn from_env() -> Result<Option<Self>> {
+        let current = match std::env::var("CAPTION_ENCRYPTION_KEY") {
+            Ok(raw) if !raw.trim().is_empty() => Some(raw),
+            _ => match std::env::var("CAPTION_ENCRYPTION_KEY_FILE") {
+                Ok(path) if !path.trim().is_empty() => Some(
+                    fs::read_to_string(path.trim())
+                        .with_context(|| format!("reading caption encryption key from {path}"))?,
+                ),
+                _ => None,
+            },
+        };
+        let Some(current) = current else {
+            return Ok(None);
+        };
+
+        let previous = std::env::var("CAPTION_ENCRYPTION_PREVIOUS_KEYS")
+            .ok()
+            .map(|raw| {
+                raw.split(',')
+                    .filter(|key| !key.trim().is_empty())
+                    .map(MasterKey::parse)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()
+            .context("parsing CAPTION_ENCRYPTION_PREVIOUS_KEYS")?
+            .unwrap_or_default();
+
+        Ok(Some(Self {
+            current: MasterKey::parse(&current).context("parsing CAPTION_ENCRYPTION_KEY")?,
+            previous,
+        }))
+    }
+
+    /// A keyring holding only `raw`, given in the `CAPTION_ENCRYPTION_KEY` format.
+    #[cfg(test)]
+    pub(crate) fn from_key(raw: &str) -> Result<Self> {
+        Ok(Self {
+            current: MasterKey::parse(raw)?,
+            previous: Vec::new(),
+        })
+    }
+
+    pub fn current_key_id(&self) -> &str {
+        &self.current.id
+    }
+
+    fn key(&self, key_id: &str) -> Option<&MasterKey> {
+        std::iter::once(&self.current)
+            .chain(self.previous.iter())
+            .find(|key| key.id == key_id)
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let data_key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let data_cipher = XChaCha20Poly1305::new(&data_key);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = data_cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("encrypting caption document"))?;
+        let key_nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let wrapped_key = self
+            .current
+            .cipher
+            .encrypt(&key_nonce, data_key.as_slice())
+            .map_err(|_| anyhow!("wrapping caption data key"))?;
+
+        let envelope = Envelope {
+            hammock_envelope: ENVELOPE_VERSION,
+            alg: ENVELOPE_ALGORITHM.to_string(),
+            key_id: self.current.id.clone(),
+            wrapped_key: BASE64.encode(wrapped_key),
+            key_nonce: BASE64.encode(key_nonce),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        Ok(serde_json::to_vec(&envelope)?)
+    }
+
+    fn open(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        if envelope.hammock_envelope != ENVELOPE_VERSION || envelope.alg != ENVELOPE_ALGORITHM {
+            bail!(
+                "unsupported caption envelope v{} ({})",
+                envelope.hammock_envelope,
+                envelope.alg
+            );
+        }
+        let master = self.key(&envelope.key_id).ok_or_else(|| {
+            anyhow!(
+                "caption file was encrypted with unknown key {}; add it to CAPTION_ENCRYPTION_PREVIOUS_KEYS",
+                envelope.key_id
+            )
+        })?;
+        let key_nonce = decode_nonce(&envelope.key_nonce)?;
+        let data_key = master
+            .cipher
+            .decrypt(&key_nonce, BASE64.decode(&envelope.wrapped_key)?.as_slice())
+            .map_err(|_| anyhow!("unwrapping caption data key {}", envelope.key_id))?;
+        let data_cipher = XChaCha20Poly1305::new_from_slice(&data_key)
+            .map_err(|_| anyhow!("invalid caption data key"))?;
+        let nonce = decode_nonce(&envelope.nonce)?;
+        data_cipher
+            .decrypt(&nonce, BASE64.decode(&envelope.ciphertext)?.as_slice())
+            .map_err(|_| anyhow!("decrypting caption document"))
+    }
+}
+
+fn decode_nonce(raw: &str) -> Result<XNonce> {
+    let bytes = BASE64.decode(raw)?;
+    if bytes.len() != 24 {
+        bail!("caption envelope nonce has invalid length {}", bytes.len());
+    }
+    Ok(*XNonce::from_slice(&bytes))
+}
+
+fn parse_envelope(bytes: &[u8]) -> Option<Envelope> {
+    let head = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .map(|idx| &bytes[idx..])?;
+    if !head.starts_with(b"{\"hammock_envelope\"") {
+        return None;
+    }
+    serde_json::from_slice(bytes).ok()
+}
+
+/// Returns the plaintext for a caption file, decrypting it when it carries an envelope.
+pub fn open_bytes(keyring: Option<&CaptionKeyring>, bytes: Vec<u8>) -> Result<Vec<u8>> {
+    match parse_envelope(&bytes) {
+        Some(envelope) => keyring
+            .ok_or_else(|| {
+                anyhow!("caption file is encrypted but CAPTION_ENCRYPTION_KEY is not configured")
+            })?
+            .open(&envelope),
+        None => Ok(bytes),
+    }
+}
+
+pub fn envelope_key_id(bytes: &[u8]) -> Option<String> {
+    parse_envelope(bytes).map(|envelope| envelope.key_id)
+}
+
+#[derive(Debug, Default)]
+pub struct RotationReport {
+    pub rewrapped: usize,
+    pub encrypted: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+}
+
+/// Re-encrypts every session document, retention archive, and stored summary in `store`
+/// with the keyring's current key. Plaintext documents are encrypted, documents sealed with
+/// a retired key are re-sealed, and documents already using the current key are left alone.
+/// Archives are decompressed, re-sealed, and compressed again.
+pub fn rotate_store(store: &dyn SessionStore, keyring: &CaptionKeyring) -> Result<RotationReport> {
+    let mut report = RotationReport::default();
+    let objects = store
+        .list()
+        .with_context(|| format!("listing caption storage {}", store.location()))?;
+    for object in objects {
+        let rotatable = object.name.ends_with(".json")
+            || object.name.ends_with(".json.gz")
+            || object.name.ends_with(SUMMARY_SUFFIX);
+        if !rotatable || object.name.starts_with('.') {
+            continue;
+        }
+        match rotate_object(store, &object.name, keyring) {
+            Ok(RotationOutcome::Unchanged) => report.unchanged += 1,
+            Ok(RotationOutcome::Rewrapped) => report.rewrapped += 1,
+            Ok(RotationOutcome::Encrypted) => report.encrypted += 1,
+            Err(err) => {
+                tracing::error!(file = %object.name, ?err, "Failed to rotate caption file");
+                report.failed += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+enum RotationOutcome {
+    Unchanged,
+    Rewrapped,
+    Encrypted,
+}
+
+fn rotate_object(
+    store: &dyn SessionStore,
+    name: &str,
+    keyring: &CaptionKeyring,
+) -> Result<RotationOutcome> {
+    let mut bytes = store
+        .read(name)?
+        .ok_or_else(|| anyhow!("{name} disappeared during rotation"))?;
+    let archived = name.ends_with(".gz");
+    if archived {
+        let mut decoded = Vec::with_capacity(bytes.len() * 4);
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decoded)
+            .with_context(|| format!("decompressing {name}"))?;
+        bytes = decoded;
+    }
+    let outcome = match envelope_key_id(&bytes) {
+        Some(key_id) if key_id == keyring.current_key_id() => {
+            return Ok(RotationOutcome::Unchanged);
+        }
+        Some(_) => RotationOutcome::Rewrapped,
+        None => RotationOutcome::Encrypted,
+    };
+    let plaintext = open_bytes(Some(keyring), bytes)?;
+    let mut sealed = keyring.seal(&plaintext)?;
+    if archived {
+        let mut encoder = GzEncoder::new(Vec::with_capacity(sealed.len()), Compression::default());
+        encoder.write_all(&sealed)?;
+        sealed = encoder.finish()?;
+    }
+    store.write(name, &sealed)?;
+    Ok(outcome)
+}
@@ -0,0 +1,560 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+use crate::summaries::StructuredSummary;
+
+use super::{
+    CaptionEntry, CaptionSink, SessionDocument, SpeakerInfo,
+    crypto::{self, CaptionKeyring},
+    index::{parse_ids, session_id_from_file_name},
+    json::{SessionMetadata, format_duration},
+    storage::StoredObject,
+};
+
+/// Schema migrations, applied in order and tracked with `PRAGMA user_version`.
+/// Append new steps; never edit one that has shipped.
+const MIGRATIONS: &[&str] = &[
+    r#"
+CREATE TABLE sessions (
+    id               TEXT PRIMARY KEY,
+    guild_id         INTEGER NOT NULL,
+    channel_id       INTEGER NOT NULL,
+    title            TEXT,
+    started_at       TEXT NOT NULL,
+    ended_at         TEXT,
+    duration_seconds INTEGER,
+    entry_count      INTEGER NOT NULL DEFAULT 0,
+    updated_at_ms    INTEGER NOT NULL
+);
+CREATE INDEX sessions_by_guild ON sessions (guild_id, started_at);
+
+CREATE TABLE participants (
+    session_id TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    name       TEXT NOT NULL,
+    user_id    INTEGER,
+    lines      INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (session_id, name)
+);
+CREATE INDEX participants_by_user ON participants (user_id);
+
+CREATE TABLE caption_entries (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id   TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    seq          INTEGER NOT NULL,
+    timestamp    TEXT NOT NULL,
+    speaker_id   INTEGER,
+    speaker_name TEXT NOT NULL,
+    comment      TEXT NOT NULL,
+    tags         TEXT NOT NULL DEFAULT '[]',
+    UNIQUE (session_id, seq)
+);
+"#,
+    r#"
+ALTER TABLE sessions ADD COLUMN summary TEXT;
+"#,
+    r#"
+ALTER TABLE sessions ADD COLUMN summary_template TEXT;
+"#,
+    r#"
+ALTER TABLE sessions ADD COLUMN text_channel_id INTEGER;
+"#,
+];
+
+/// Whether the JSON session documents are still written next to the database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptDbMode {
+    /// JSON documents stay authoritative; the database receives a copy of every write.
+    Mirror,
+    /// Only the database is written; JSON is rendered from it on export.
+    Primary,
+}
+
+impl TranscriptDbMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "mirror" | "alongside" | "both" => Some(Self::Mirror),
+            "primary" | "only" | "instead" => Some(Self::Primary),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Mirror => "mirror",
+            Self::Primary => "primary",
+        }
+    }
+}
+
+/// Identifies the session row a write belongs to.
+pub struct SessionRecord<'a> {
+    pub id: &'a str,
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub metadata: &'a SessionMetadata,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// SQLite store for sessions, participants, and caption entries.
+#[derive(Debug)]
+pub struct TranscriptDatabase {
+    conn: Mutex<Connection>,
+    /// Seals caption text and summaries; metadata columns stay queryable.
+    keyring: Option<Arc<CaptionKeyring>>,
+}
+
+impl TranscriptDatabase {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let mut conn = Connection::open(path)
+            .with_context(|| format!("opening transcript database {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            keyring: None,
+        })
+    }
+
+    /// Stores caption comments and summaries as caption encryption envelopes. Rows
+    /// written before a keyring was configured are still read as plaintext.
+    pub fn with_keyring(mut self, keyring: Option<Arc<CaptionKeyring>>) -> Self {
+        self.keyring = keyring;
+        self
+    }
+
+    /// Creates the session row or refreshes its metadata.
+    pub fn upsert_session(&self, record: &SessionRecord<'_>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        upsert_session(&conn, record)?;
+        Ok(())
+    }
+
+    pub fn append_entry(&self, record: &SessionRecord<'_>, entry: &CaptionEntry) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO sessions (id, guild_id, channel_id, title, started_at, updated_at_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                record.id,
+                record.guild_id.get() as i64,
+                record.channel_id.get() as i64,
+                record.metadata.title,
+                record.metadata.started_at,
+                now_ms(),
+            ],
+        )?;
+        let seq: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(seq) + 1, 0) FROM caption_entries WHERE session_id = ?1",
+            params![record.id],
+            |row| row.get(0),
+        )?;
+        insert_entry(&tx, self.keyring.as_deref(), record.id, seq, entry)?;
+        tx.execute(
+            "UPDATE sessions SET entry_count = entry_count + 1, updated_at_ms = ?2 WHERE id = ?1",
+            params![record.id, now_ms()],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Mirrors `CaptionSink::relabel_placeholder`; returns whether any entry changed.
+    pub fn relabel_placeholder(
+        &self,
+        session_id: &str,
+        placeholder: &str,
+        new_id: UserId,
+        new_name: &str,
+    ) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let changed = tx.execute(
+            "UPDATE caption_entries SET speaker_id = ?3, speaker_name = ?4
+             WHERE session_id = ?1 AND speaker_id IS NULL AND speaker_name = ?2",
+            params![session_id, placeholder, new_id.get() as i64, new_name],
+        )?;
+        if changed > 0 {
+            rebuild_participants(&tx, session_id)?;
+            tx.execute(
+                "UPDATE sessions SET updated_at_ms = ?2 WHERE id = ?1",
+                params![session_id, now_ms()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(changed > 0)
+    }
+
+    /// Removes a session with its participants and caption entries.
+    pub fn delete_session(&self, session_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM sessions WHERE id = ?1", params![session_id])?;
+        Ok(())
+    }
+
+    /// Stores the structured summary JSON on the session row.
+    pub fn set_summary(&self, session_id: &str, summary: &StructuredSummary) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET summary = ?2, updated_at_ms = ?3 WHERE id = ?1",
+            params![
+                session_id,
+                seal_text(self.keyring.as_deref(), &serde_json::to_string(summary)?)?,
+                now_ms()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Renders a stored session in the JSON document format.
+    pub fn load_document(&self, session_id: &str) -> Result<Option<SessionDocument>> {
+        let conn = self.conn.lock().unwrap();
+        let row = conn
+            .query_row(
+                "SELECT title, started_at, ended_at, duration_seconds, summary, summary_template,
+                        text_channel_id
+                 FROM sessions WHERE id = ?1",
+                params![session_id],
+                |row| {
+                    let duration_seconds: Option<i64> = row.get(3)?;
+                    let duration_seconds = duration_seconds.map(|secs| secs.max(0) as u64);
+                    let summary: Option<String> = row.get(4)?;
+                    let metadata = SessionMetadata {
+                        title: row.get(0)?,
+                        started_at: row.get(1)?,
+                        ended_at: row.get(2)?,
+                        duration_seconds,
+                        duration_formatted: duration_seconds
+                            .map(|secs| format_duration(Duration::from_secs(secs))),
+                        summary_template: row.get(5)?,
+                        text_channel_id: row
+                            .get::<_, Option<i64>>(6)?
+                            .and_then(|id| u64::try_from(id).ok())
+                            .filter(|id| *id > 0)
+                            .map(ChannelId::new),
+                    };
+                    Ok((metadata, summary))
+                },
+            )
+            .optional()?;
+        let Some((metadata, summary)) = row else {
+            return Ok(None);
+        };
+
+        let mut statement = conn.prepare(
+            "SELECT timestamp, speaker_id, speaker_name, comment, tags
+             FROM caption_entries WHERE session_id = ?1 ORDER BY seq",
+        )?;
+        let transcriptions = statement
+            .query_map(params![session_id], |row| {
+                let speaker_id: Option<i64> = row.get(1)?;
+                let tags: String = row.get(4)?;
+                Ok(CaptionEntry {
+                    timestamp: row.get(0)?,
+                    speaker: SpeakerInfo {
+                        id: speaker_id
+                            .filter(|id| *id > 0)
+                            .map(|id| UserId::new(id as u64)),
+                        name: row.get(2)?,
+                    },
+                    comment: row.get(3)?,
+                    tags: serde_json::from_str(&tags).unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|mut entry| {
+                entry.comment = open_text(self.keyring.as_deref(), entry.comment)?;
+                Ok(entry)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let summary = summary
+            .map(|raw| open_text(self.keyring.as_deref(), raw))
+            .transpose()?;
+
+        Ok(Some(SessionDocument {
+            metadata,
+            transcriptions,
+            summary: summary.and_then(|raw| serde_json::from_str(&raw).ok()),
+        }))
+    }
+
+    /// Sessions in the same shape as a storage listing (`<id>.json`), so the session
+    /// index can scan either. `size` is the entry count, which is enough for change
+    /// detection.
+    pub fn list_sessions(&self) -> Result<Vec<StoredObject>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT id, entry_count, updated_at_ms FROM sessions")?;
+        let sessions = statement
+            .query_map([], |row| {
+                let id: String = row.get(0)?;
+                let entries: i64 = row.get(1)?;
+                let updated_at_ms: i64 = row.get(2)?;
+                Ok(StoredObject {
+                    name: format!("{id}.json"),
+                    size: entries.max(0) as u64,
+                    modified: UNIX_EPOCH + Duration::from_millis(updated_at_ms.max(0) as u64),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sessions)
+    }
+
+    /// Stores a whole document, replacing any existing rows for the session when
+    /// `replace` is set. Returns `false` when the session existed and was left alone.
+    pub fn import_document(
+        &self,
+        record: &SessionRecord<'_>,
+        document: &SessionDocument,
+        replace: bool,
+    ) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let exists = tx
+            .query_row(
+                "SELECT 1 FROM sessions WHERE id = ?1",
+                params![record.id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if exists && !replace {
+            return Ok(false);
+        }
+        tx.execute("DELETE FROM sessions WHERE id = ?1", params![record.id])?;
+        upsert_session(&tx, record)?;
+        for (seq, entry) in document.transcriptions.iter().enumerate() {
+            insert_entry(&tx, self.keyring.as_deref(), record.id, seq as i64, entry)?;
+        }
+        let summary = document
+            .summary
+            .as_ref()
+            .map(|summary| seal_text(self.keyring.as_deref(), &serde_json::to_string(summary)?))
+            .transpose()?;
+        tx.execute(
+            "UPDATE sessions SET entry_count = ?2, summary = ?3 WHERE id = ?1",
+            params![record.id, document.transcriptions.len() as i64, summary],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Imports every JSON session document in the sink's storage (including retention
+    /// archives). Sessions already in the database are skipped unless `replace` is set.
+    pub fn import_from(&self, sink: &CaptionSink, replace: bool) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        for object in sink.store().list()? {
+            let Some((id, _)) = session_id_from_file_name(&object.name) else {
+                continue;
+            };
+            let Some((guild_id, channel_id)) = parse_ids(&id) else {
+                continue;
+            };
+            let result = sink
+                .read_session_document(&object.name)
+                .and_then(|document| {
+                    let record = SessionRecord {
+                        id: &id,
+                        guild_id,
+                        channel_id,
+                        metadata: &document.metadata,
+                    };
+                    self.import_document(&record, &document, replace)
+                });
+            match result {
+                Ok(true) => report.imported += 1,
+                Ok(false) => report.skipped += 1,
+                Err(err) => {
+                    tracing::error!(file = %object.name, ?err, "Failed to import session");
+                    report.failed += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let applied: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(applied.max(0) as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)
+            .with_context(|| format!("applying transcript database migration {}", idx + 1))?;
+        tx.pragma_update(None, "user_version", (idx + 1) as i64)?;
+        tx.commit()?;
+        tracing::info!(version = idx + 1, "Applied transcript database migration");
+    }
+    Ok(())
+}
+
+fn upsert_session(conn: &Connection, record: &SessionRecord<'_>) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO sessions (id, guild_id, channel_id, title, started_at, ended_at, duration_seconds, updated_at_ms, summary_template, text_channel_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT (id) DO UPDATE SET
+             title = excluded.title,
+             summary_template = excluded.summary_template,
+             text_channel_id = excluded.text_channel_id,
+             started_at = excluded.started_at,
+             ended_at = excluded.ended_at,
+             duration_seconds = excluded.duration_seconds,
+             updated_at_ms = excluded.updated_at_ms",
+        params![
+            record.id,
+            record.guild_id.get() as i64,
+            record.channel_id.get() as i64,
+            record.metadata.title,
+            record.metadata.started_at,
+            record.metadata.ended_at,
+            record.metadata.duration_seconds.map(|secs| secs as i64),
+            now_ms(),
+            record.metadata.summary_template,
+            record
+                .metadata
+                .text_channel_id
+                .map(|channel| channel.get() as i64),
+        ],
+    )
+}
+
+fn insert_entry(
+    conn: &Connection,
+    keyring: Option<&CaptionKeyring>,
+    session_id: &str,
+    seq: i64,
+    entry: &CaptionEntry,
+) -> Result<()> {
+    let speaker_id = entry.speaker.id.map(|id| id.get() as i64);
+    conn.execute(
+        "INSERT INTO caption_entries (session_id, seq, timestamp, speaker_id, speaker_name, comment, tags)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            session_id,
+            seq,
+            entry.timestamp,
+            speaker_id,
+            entry.speaker.name,
+            seal_text(keyring, &entry.comment)?,
+            serde_json::to_string(&entry.tags)?,
+        ],
+    )?;
+    conn.execute(
+        "INSERT INTO participants (session_id, name, user_id, lines) VALUES (?1, ?2, ?3, 1)
+         ON CONFLICT (session_id, name) DO UPDATE SET
+             lines = lines + 1,
+             user_id = COALESCE(participants.user_id, excluded.user_id)",
+        params![session_id, entry.speaker.name, speaker_id],
+    )?;
+    Ok(())
+}
+
+fn rebuild_participants(conn: &Connection, session_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM participants WHERE session_id = ?1",
+        params![session_id],
+    )?;
+    conn.execute(
+        "INSERT INTO participants (session_id, name, user_id, lines)
+         SELECT session_id, speaker_name, MAX(speaker_id), COUNT(*)
+         FROM caption_entries WHERE session_id = ?1
+         GROUP BY speaker_name",
+        params![session_id],
+    )?;
+    Ok(())
+}
+
+/// The caption encryption envelope of `text`, or `text` itself without a keyring.
+fn seal_text(keyring: Option<&CaptionKeyring>, text: &str) -> Result<String> {
+    match keyring {
+        Some(keyring) => Ok(String::from_utf8(keyring.seal(text.as_bytes())?)?),
+        None => Ok(text.to_string()),
+    }
+}
+
+fn open_text(keyring: Option<&CaptionKeyring>, stored: String) -> Result<String> {
+    let bytes = crypto::open_bytes(keyring, stored.into_bytes())?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> SessionMetadata {
+        SessionMetadata {
+            title: Some("Standup".to_string()),
+            started_at: "2026-01-05T09:00:00+00:00".to_string(),
+            ended_at: None,
+            duration_seconds: None,
+            duration_formatted: None,
+            summary_template: None,
+            text_channel_id: None,
+        }
+    }
+
+    #[test]
+    fn seals_comments_with_the_keyring() {
+        let keyring = Arc::new(CaptionKeyring::from_key(&"22".repeat(32)).unwrap());
+        let database = TranscriptDatabase::open(Path::new(":memory:"))
+            .unwrap()
+            .with_keyring(Some(keyring));
+        let metadata = metadata();
+        let record = SessionRecord {
+            id: "1_2_20260105_090000",
+            guild_id: GuildId::new(1),
+            channel_id: ChannelId::new(2),
+            metadata: &metadata,
+        };
+        let entry = CaptionEntry {
+            timestamp: "2026-01-05T09:00:05+00:00".to_string(),
+            speaker: SpeakerInfo {
+                id: Some(UserId::new(3)),
+                name: "Ana".to_string(),
+            },
+            comment: "my card is 4111 1111 1111 1111".to_string(),
+            tags: Vec::new(),
+        };
+        database.upsert_session(&record).unwrap();
+        database.append_entry(&record, &entry).unwrap();
+
+        let stored: String = database
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT comment FROM caption_entries", [], |row| row.get(0))
+            .unwrap();
+        assert!(stored.starts_with("{\"hammock_envelope\""));
+        assert!(!stored.contains("4111"));
+
+        let document = database.load_document(record.id).unwrap().unwrap();
+        assert_eq!(document.transcriptions[0].comment, entry.comment);
+    }
+}
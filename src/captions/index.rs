@@ -0,0 +1,577 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::Serialize;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use utoipa::ToSchema;
+
+use crate::redaction::Redactor;
+
+use super::{CaptionSink, SessionDocument};
+
+const ARCHIVE_SUFFIX: &str = ".json.gz";
+/// Rendered summaries are stored beside their session under `<session id>.summary.md`;
+/// the suffix keeps them out of session listings.
+pub const SUMMARY_SUFFIX: &str = ".summary.md";
+/// A question term found only in a line's speaker name counts this much of a term
+/// found in the spoken text.
+const SPEAKER_MATCH_WEIGHT: f64 = 0.5;
+/// Words that carry no meaning for retrieval ("what did Sam say about …").
+const STOP_WORDS: &[&str] = &[
+    "a",
+    "about",
+    "and",
+    "any",
+    "anyone",
+    "are",
+    "at",
+    "be",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "for",
+    "from",
+    "had",
+    "has",
+    "have",
+    "he",
+    "her",
+    "him",
+    "his",
+    "how",
+    "i",
+    "in",
+    "is",
+    "it",
+    "its",
+    "me",
+    "mention",
+    "mentioned",
+    "of",
+    "on",
+    "or",
+    "our",
+    "said",
+    "say",
+    "says",
+    "she",
+    "should",
+    "so",
+    "talk",
+    "talked",
+    "tell",
+    "that",
+    "the",
+    "their",
+    "them",
+    "there",
+    "they",
+    "this",
+    "to",
+    "us",
+    "was",
+    "we",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "who",
+    "why",
+    "will",
+    "with",
+    "would",
+    "you",
+];
+
+/// In-memory index of every session document in the caption store. The index is
+/// refreshed lazily: documents are only re-parsed when their size or mtime changes.
+///
+/// Lines are export-redacted as they are indexed, so search hits never carry masked
+/// PII and searching for it (`@gmail.com`) matches nothing.
+pub struct SessionIndex {
+    sink: Arc<CaptionSink>,
+    redactor: Arc<Redactor>,
+    sessions: RwLock<HashMap<String, IndexedSession>>,
+    /// Externally reachable control plane URL used for `session_url` links.
+    public_base_url: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexedSession {
+    pub id: String,
+    pub file_name: String,
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub title: Option<String>,
+    pub started_at: Option<DateTime<FixedOffset>>,
+    pub ended_at: Option<DateTime<FixedOffset>>,
+    pub duration_formatted: Option<String>,
+    pub participants: Vec<String>,
+    pub participant_ids: Vec<UserId>,
+    pub archived: bool,
+    pub lines: Vec<IndexedLine>,
+    modified: SystemTime,
+    size: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexedLine {
+    pub timestamp: String,
+    pub speaker: String,
+    pub speaker_id: Option<UserId>,
+    pub comment: String,
+    normalized: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchQuery {
+    pub guild_id: Option<GuildId>,
+    pub text: String,
+    pub channel_id: Option<ChannelId>,
+    pub speaker_id: Option<UserId>,
+    pub since: Option<DateTime<FixedOffset>>,
+    pub until: Option<DateTime<FixedOffset>>,
+    pub limit: usize,
+}
+
+/// A free-text question to find supporting caption lines for.
+#[derive(Clone, Debug, Default)]
+pub struct RetrievalQuery {
+    pub guild_id: Option<GuildId>,
+    pub session_id: Option<String>,
+    pub speaker_id: Option<UserId>,
+    pub since: Option<DateTime<FixedOffset>>,
+    pub question: String,
+    /// Best-matching lines to keep.
+    pub limit: usize,
+    /// Neighbouring lines returned on each side of a match.
+    pub context: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct RetrievedLine {
+    pub session_id: String,
+    pub session_title: Option<String>,
+    pub timestamp: String,
+    pub speaker: String,
+    pub comment: String,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub session_title: Option<String>,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub timestamp: String,
+    pub speaker: String,
+    pub comment: String,
+    pub channel_url: String,
+    /// Position of the line in the session document's `transcriptions` array.
+    pub entry_index: usize,
+    /// `/api/sessions/{id}` link to the session document when `PUBLIC_BASE_URL` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_url: Option<String>,
+}
+
+impl SessionIndex {
+    pub fn new(sink: Arc<CaptionSink>, redactor: Arc<Redactor>) -> Self {
+        Self {
+            sink,
+            redactor,
+            sessions: RwLock::new(HashMap::new()),
+            public_base_url: None,
+        }
+    }
+
+    pub fn with_public_base_url(mut self, public_base_url: Option<String>) -> Self {
+        self.public_base_url = public_base_url;
+        self
+    }
+
+    /// Re-scans the caption store, parsing new or modified session documents and
+    /// dropping entries whose documents disappeared.
+    pub fn refresh(&self) -> Result<()> {
+        let mut seen = HashSet::new();
+        for object in self.sink.list_sessions()? {
+            let Some((id, archived)) = session_id_from_file_name(&object.name) else {
+                continue;
+            };
+            let modified = object.modified;
+            let size = object.size;
+            seen.insert(id.clone());
+
+            let unchanged = self
+                .sessions
+                .read()
+                .unwrap()
+                .get(&id)
+                .is_some_and(|existing| existing.modified == modified && existing.size == size);
+            if unchanged {
+                continue;
+            }
+
+            match self.sink.read_session_document(&object.name) {
+                Ok(mut document) => {
+                    self.redactor.redact_document_for_export(&mut document);
+                    if let Some(session) = IndexedSession::from_document(
+                        &id,
+                        object.name.clone(),
+                        archived,
+                        document,
+                        modified,
+                        size,
+                    ) {
+                        self.sessions.write().unwrap().insert(id, session);
+                    }
+                }
+                Err(err) => {
+                    tracing::debug!(file = %object.name, ?err, "Skipping unreadable session file");
+                }
+            }
+        }
+
+        self.sessions
+            .write()
+            .unwrap()
+            .retain(|id, _| seen.contains(id));
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<IndexedSession> {
+        self.sessions.read().unwrap().get(id).cloned()
+    }
+
+    /// Sessions for a guild, newest first.
+    pub fn sessions_for_guild(&self, guild_id: GuildId) -> Vec<IndexedSession> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .read()
+            .unwrap()
+            .values()
+            .filter(|session| session.guild_id == guild_id)
+            .cloned()
+            .collect();
+        sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at).then(b.id.cmp(&a.id)));
+        sessions
+    }
+
+    /// Returns caption lines containing every term of the query, newest sessions first.
+    pub fn search(&self, query: &SearchQuery) -> Vec<SearchHit> {
+        let terms: Vec<String> = query
+            .text
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let sessions = self.sessions.read().unwrap();
+        let mut candidates: Vec<&IndexedSession> = sessions
+            .values()
+            .filter(|session| query.guild_id.is_none_or(|guild| session.guild_id == guild))
+            .filter(|session| {
+                query
+                    .channel_id
+                    .is_none_or(|channel| session.channel_id == channel)
+            })
+            .filter(|session| match (query.since, session.started_at) {
+                (Some(since), Some(started)) => started >= since,
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .filter(|session| match (query.until, session.started_at) {
+                (Some(until), Some(started)) => started <= until,
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.started_at.cmp(&a.started_at).then(b.id.cmp(&a.id)));
+
+        let limit = if query.limit == 0 { 25 } else { query.limit };
+        let mut hits = Vec::new();
+        for session in candidates {
+            let title = session
+                .title
+                .as_deref()
+                .map(str::to_lowercase)
+                .unwrap_or_default();
+            for (entry_index, line) in session.lines.iter().enumerate() {
+                if query
+                    .speaker_id
+                    .is_some_and(|speaker| line.speaker_id != Some(speaker))
+                {
+                    continue;
+                }
+                let matches = terms
+                    .iter()
+                    .all(|term| line.normalized.contains(term) || title.contains(term));
+                if !matches {
+                    continue;
+                }
+                hits.push(session.hit(line, entry_index, self.public_base_url.as_deref()));
+                if hits.len() >= limit {
+                    return hits;
+                }
+            }
+        }
+        hits
+    }
+
+    /// Finds the lines most relevant to a question, each with its neighbouring lines,
+    /// grouped by session (newest first) in spoken order. Lines are ranked by the
+    /// rarity-weighted question terms they contain, so a term that appears everywhere
+    /// counts for little.
+    pub fn retrieve(&self, query: &RetrievalQuery) -> Vec<RetrievedLine> {
+        let terms = question_terms(&query.question);
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let sessions = self.sessions.read().unwrap();
+        let mut candidates: Vec<&IndexedSession> = sessions
+            .values()
+            .filter(|session| query.guild_id.is_none_or(|guild| session.guild_id == guild))
+            .filter(|session| {
+                query
+                    .session_id
+                    .as_deref()
+                    .is_none_or(|id| session.id == id)
+            })
+            .filter(|session| match (query.since, session.started_at) {
+                (Some(since), Some(started)) => started >= since,
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.started_at.cmp(&a.started_at).then(b.id.cmp(&a.id)));
+
+        let eligible = |line: &IndexedLine| {
+            query
+                .speaker_id
+                .is_none_or(|speaker| line.speaker_id == Some(speaker))
+        };
+        let weight = |line: &IndexedLine, term: &str| {
+            if line.normalized.contains(term) {
+                1.0
+            } else if line
+                .speaker
+                .to_lowercase()
+                .split_whitespace()
+                .any(|word| word == term)
+            {
+                SPEAKER_MATCH_WEIGHT
+            } else {
+                0.0
+            }
+        };
+
+        let mut total = 0usize;
+        let mut frequency = vec![0usize; terms.len()];
+        for line in candidates
+            .iter()
+            .flat_map(|session| &session.lines)
+            .filter(|line| eligible(line))
+        {
+            total += 1;
+            for (count, term) in frequency.iter_mut().zip(&terms) {
+                if weight(line, term) > 0.0 {
+                    *count += 1;
+                }
+            }
+        }
+        let rarity: Vec<f64> = frequency
+            .iter()
+            .map(|count| ((total as f64 + 1.0) / (*count as f64 + 0.5)).ln().max(0.0))
+            .collect();
+
+        let mut scored = Vec::new();
+        for (position, session) in candidates.iter().enumerate() {
+            for (index, line) in session.lines.iter().enumerate() {
+                if !eligible(line) {
+                    continue;
+                }
+                let score: f64 = terms
+                    .iter()
+                    .zip(&rarity)
+                    .map(|(term, rarity)| weight(line, term) * rarity)
+                    .sum();
+                if score > 0.0 {
+                    scored.push((score, position, index));
+                }
+            }
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+        scored.truncate(if query.limit == 0 { 25 } else { query.limit });
+
+        let mut selected: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for (_, position, index) in scored {
+            let last = candidates[position].lines.len() - 1;
+            selected
+                .entry(position)
+                .or_default()
+                .extend(index.saturating_sub(query.context)..=(index + query.context).min(last));
+        }
+        selected
+            .into_iter()
+            .flat_map(|(position, indices)| {
+                let session = candidates[position];
+                indices.into_iter().map(move |index| {
+                    let line = &session.lines[index];
+                    RetrievedLine {
+                        session_id: session.id.clone(),
+                        session_title: session.title.clone(),
+                        timestamp: line.timestamp.clone(),
+                        speaker: line.speaker.clone(),
+                        comment: line.comment.clone(),
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+impl IndexedSession {
+    fn from_document(
+        id: &str,
+        file_name: String,
+        archived: bool,
+        document: SessionDocument,
+        modified: SystemTime,
+        size: u64,
+    ) -> Option<Self> {
+        let (guild_id, channel_id) = parse_ids(id)?;
+        let mut participants = Vec::new();
+        let mut participant_ids = Vec::new();
+        let lines = document
+            .transcriptions
+            .into_iter()
+            .map(|entry| {
+                if !participants.contains(&entry.speaker.name) {
+                    participants.push(entry.speaker.name.clone());
+                }
+                if let Some(id) = entry.speaker.id
+                    && !participant_ids.contains(&id)
+                {
+                    participant_ids.push(id);
+                }
+                IndexedLine {
+                    normalized: entry.comment.to_lowercase(),
+                    timestamp: entry.timestamp,
+                    speaker: entry.speaker.name,
+                    speaker_id: entry.speaker.id,
+                    comment: entry.comment,
+                }
+            })
+            .collect();
+
+        Some(Self {
+            id: id.to_string(),
+            file_name,
+            guild_id,
+            channel_id,
+            title: document.metadata.title,
+            started_at: DateTime::parse_from_rfc3339(&document.metadata.started_at).ok(),
+            ended_at: document
+                .metadata
+                .ended_at
+                .as_deref()
+                .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok()),
+            duration_formatted: document.metadata.duration_formatted,
+            participants,
+            participant_ids,
+            archived,
+            lines,
+            modified,
+            size,
+        })
+    }
+
+    pub fn channel_url(&self) -> String {
+        format!(
+            "https://discord.com/channels/{}/{}",
+            self.guild_id, self.channel_id
+        )
+    }
+
+    fn hit(&self, line: &IndexedLine, entry_index: usize, base_url: Option<&str>) -> SearchHit {
+        SearchHit {
+            session_id: self.id.clone(),
+            session_title: self.title.clone(),
+            guild_id: self.guild_id.to_string(),
+            channel_id: self.channel_id.to_string(),
+            timestamp: line.timestamp.clone(),
+            speaker: line.speaker.clone(),
+            comment: line.comment.clone(),
+            channel_url: self.channel_url(),
+            entry_index,
+            session_url: base_url
+                .map(|base| format!("{}/api/sessions/{}", base.trim_end_matches('/'), self.id)),
+        }
+    }
+}
+
+/// Lowercased, de-duplicated question words minus stop words.
+fn question_terms(question: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in question
+        .split(|ch: char| !ch.is_alphanumeric())
+        .map(str::to_lowercase)
+    {
+        if word.chars().count() < 2 || STOP_WORDS.contains(&word.as_str()) {
+            continue;
+        }
+        if !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Accepts either an RFC 3339 timestamp or a `YYYY-MM-DD` date (interpreted in UTC,
+/// at the start of the day or, with `end_of_day`, at its last second).
+pub fn parse_date_bound(raw: &str, end_of_day: bool) -> Option<DateTime<FixedOffset>> {
+    let raw = raw.trim();
+    if let Ok(value) = DateTime::parse_from_rfc3339(raw) {
+        return Some(value);
+    }
+    let date = NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok()?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)?
+    } else {
+        date.and_hms_opt(0, 0, 0)?
+    };
+    Some(time.and_utc().fixed_offset())
+}
+
+/// Maps `<guild>_<channel>_<date>_<time>[_slug].json(.gz)` to its session id (the
+/// file name without extension).
+pub fn session_id_from_file_name(file_name: &str) -> Option<(String, bool)> {
+    let (stem, archived) = if let Some(stem) = file_name.strip_suffix(ARCHIVE_SUFFIX) {
+        (stem, true)
+    } else {
+        (file_name.strip_suffix(".json")?, false)
+    };
+    parse_ids(stem)?;
+    Some((stem.to_string(), archived))
+}
+
+pub fn summary_file_name(session_id: &str) -> String {
+    format!("{session_id}{SUMMARY_SUFFIX}")
+}
+
+pub(super) fn parse_ids(id: &str) -> Option<(GuildId, ChannelId)> {
+    let mut parts = id.split('_');
+    let guild = parts.next()?.parse::<u64>().ok().filter(|id| *id > 0)?;
+    let channel = parts.next()?.parse::<u64>().ok().filter(|id| *id > 0)?;
+    Some((GuildId::new(guild), ChannelId::new(channel)))
+}
@@ -1,23 +1,84 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use chrono::{DateTime, Local, SecondsFormat, Timelike};
 use dashmap::{DashMap, mapref::entry::Entry};
+use flate2::read::GzDecoder;
 use serde::{Deserialize, Serialize};
 use serenity::model::id::{ChannelId, GuildId, UserId};
-use std::fs;
-use std::io::BufWriter;
-use std::path::{Path, PathBuf};
+use std::collections::HashSet;
+use std::io::Read;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::summaries::StructuredSummary;
+
+use super::{
+    crypto::{self, CaptionKeyring},
+    database::{SessionRecord, TranscriptDatabase},
+    index::{session_id_from_file_name, summary_file_name},
+    storage::{SessionStore, StoredObject},
+};
+
+const CAPTION_EVENT_CAPACITY: usize = 256;
+/// How often the draft flusher looks for documents due to be written.
+const DRAFT_FLUSH_TICK: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 pub struct CaptionSink {
-    pub root: PathBuf,
+    store: Arc<dyn SessionStore>,
     sessions: DashMap<(GuildId, ChannelId), SessionInfo>,
+    /// Documents of active sessions held in memory while the store buffers appends, keyed
+    /// by file name.
+    drafts: DashMap<String, DraftDocument>,
+    keyring: Option<Arc<CaptionKeyring>>,
+    database: Option<Arc<TranscriptDatabase>>,
+    /// False when the database replaces JSON documents entirely.
+    json_files: bool,
+    events: broadcast::Sender<CaptionEvent>,
+}
+
+/// Published after every entry that was successfully written to a session file.
+#[derive(Debug, Clone)]
+pub struct CaptionEvent {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub entry: CaptionEntry,
+}
+
+/// An active session's document, possibly holding lines not yet written to the store.
+#[derive(Debug)]
+struct DraftDocument {
+    document: SessionDocument,
+    /// Bumped on every edit; the draft is dirty while it differs from `flushed_revision`.
+    revision: u64,
+    flushed_revision: u64,
+    flushed_at: Instant,
+    /// Serializes writes of this draft so an older snapshot never lands after a newer one.
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl DraftDocument {
+    fn new(document: SessionDocument) -> Self {
+        Self {
+            document,
+            revision: 0,
+            flushed_revision: 0,
+            flushed_at: Instant::now(),
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.revision != self.flushed_revision
+    }
 }
 
 #[derive(Debug, Clone)]
 struct SessionInfo {
     file_name: String,
     title: Option<String>,
+    summary_template: Option<String>,
+    text_channel: Option<ChannelId>,
     started_at: DateTime<Local>,
     started_instant: Instant,
 }
@@ -32,20 +93,39 @@ pub struct SessionMetadata {
     pub duration_seconds: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_formatted: Option<String>,
+    /// Summary template chosen at `/join`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_template: Option<String>,
+    /// Text channel `/join` was run from; the transcript and summary are posted there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_channel_id: Option<ChannelId>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionDocument {
     pub metadata: SessionMetadata,
     pub transcriptions: Vec<CaptionEntry>,
+    /// Set once a structured summary has been generated for the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<StructuredSummary>,
+}
+
+/// A session that is still being recorded.
+#[derive(Debug, Clone)]
+pub struct ActiveSession {
+    pub file_name: String,
+    pub title: Option<String>,
+    pub started_at: DateTime<Local>,
+    pub text_channel: Option<ChannelId>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SessionSummary {
-    pub file_path: PathBuf,
+    pub file_name: String,
     pub title: Option<String>,
     pub started_at: DateTime<Local>,
     pub duration: Duration,
+    pub text_channel: Option<ChannelId>,
 }
 
 impl SessionSummary {
@@ -56,17 +136,26 @@ impl SessionSummary {
     pub fn date_label(&self) -> String {
         self.started_at.format("%m/%d/%Y").to_string()
     }
+
+    /// Identifier used by the session index and admin API.
+    pub fn session_id(&self) -> String {
+        session_id_from_file_name(&self.file_name)
+            .map(|(id, _)| id)
+            .unwrap_or_default()
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CaptionEntry {
     #[serde(with = "speaker_field")]
     pub speaker: SpeakerInfo,
     pub comment: String,
     pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpeakerInfo {
     #[serde(default, with = "optional_user_id")]
     pub id: Option<UserId>,
@@ -128,11 +217,90 @@ mod speaker_field {
 }
 
 impl CaptionSink {
-    pub fn new(root: PathBuf) -> Self {
+    pub fn new(store: Arc<dyn SessionStore>, keyring: Option<Arc<CaptionKeyring>>) -> Self {
+        let (events, _) = broadcast::channel(CAPTION_EVENT_CAPACITY);
         Self {
-            root,
+            store,
             sessions: DashMap::new(),
+            drafts: DashMap::new(),
+            keyring,
+            database: None,
+            json_files: true,
+            events,
+        }
+    }
+
+    /// Also records sessions in `database`. With `json_files` off the database becomes
+    /// the only store and JSON documents are rendered from it on read.
+    pub fn with_database(mut self, database: Arc<TranscriptDatabase>, json_files: bool) -> Self {
+        self.database = Some(database);
+        self.json_files = json_files;
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<CaptionEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn store(&self) -> &Arc<dyn SessionStore> {
+        &self.store
+    }
+
+    /// True when the database replaces JSON documents, so sessions exist only as rows.
+    pub fn database_is_primary(&self) -> bool {
+        self.primary_database().is_some()
+    }
+
+    /// Deletes the session's database rows, if the database is enabled.
+    pub fn delete_database_session(&self, session_id: &str) -> Result<()> {
+        match self.database.as_deref() {
+            Some(database) => database.delete_session(session_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Session documents available to the index: the storage listing, or the database
+    /// rows when it replaces JSON files.
+    pub fn list_sessions(&self) -> Result<Vec<StoredObject>> {
+        match self.primary_database() {
+            Some(database) => database.list_sessions(),
+            None => self.store.list(),
+        }
+    }
+
+    /// Reads a stored session and returns its plaintext JSON, decompressing retention
+    /// archives and decrypting envelopes as needed.
+    pub fn read_session_bytes(&self, file_name: &str) -> Result<Vec<u8>> {
+        if let Some(database) = self.primary_database() {
+            let document = session_id_from_file_name(file_name)
+                .map(|(id, _)| database.load_document(&id))
+                .transpose()?
+                .flatten()
+                .ok_or_else(|| anyhow!("session {file_name} not found in the database"))?;
+            return Ok(serde_json::to_vec_pretty(&document)?);
+        }
+        if let Some(draft) = self.drafts.get(file_name) {
+            return Ok(serde_json::to_vec_pretty(&draft.document)?);
         }
+        let bytes = self
+            .store
+            .read(file_name)?
+            .ok_or_else(|| anyhow!("session {file_name} not found in storage"))?;
+        self.open_session_bytes(file_name, bytes)
+    }
+
+    pub fn read_session_document(&self, file_name: &str) -> Result<SessionDocument> {
+        let bytes = self.read_session_bytes(file_name)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn open_session_bytes(&self, file_name: &str, mut bytes: Vec<u8>) -> Result<Vec<u8>> {
+        if file_name.ends_with(".gz") {
+            let mut decoded = Vec::with_capacity(bytes.len() * 4);
+            GzDecoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+            bytes = decoded;
+        }
+        crypto::open_bytes(self.keyring.as_deref(), bytes)
     }
 
     pub fn start_session(
@@ -140,8 +308,9 @@ impl CaptionSink {
         guild_id: GuildId,
         channel_id: ChannelId,
         title: Option<String>,
-    ) -> Result<PathBuf> {
-        fs::create_dir_all(&self.root)?;
+        summary_template: Option<String>,
+        text_channel: Option<ChannelId>,
+    ) -> Result<String> {
         let now = Local::now();
         let clean_title = title.and_then(|value| {
             let trimmed = value.trim();
@@ -156,13 +325,20 @@ impl CaptionSink {
         let info = SessionInfo {
             file_name: file_name.clone(),
             title: clean_title,
+            summary_template,
+            text_channel,
             started_at: now,
             started_instant: Instant::now(),
         };
-        let path = self.root.join(&file_name);
-        self.write_session_document(&path, &SessionDocument::new(&info))?;
+        if self.json_files {
+            self.write_session_document(&file_name, &SessionDocument::new(&info))?;
+        }
+        let metadata = info.initial_metadata();
+        self.write_database("start session", |database| {
+            database.upsert_session(&info.record(guild_id, channel_id, &metadata))
+        })?;
         self.sessions.insert((guild_id, channel_id), info);
-        Ok(path)
+        Ok(file_name)
     }
 
     pub fn end_session(
@@ -170,27 +346,146 @@ impl CaptionSink {
         guild_id: GuildId,
         channel_id: ChannelId,
     ) -> Result<Option<SessionSummary>> {
-        if let Some((_, info)) = self.sessions.remove(&(guild_id, channel_id)) {
-            let file_path = self.root.join(&info.file_name);
-            let mut document = self.load_session_document(&file_path, Some(&info))?;
+        if let Some(info) = self.session_info_snapshot(guild_id, channel_id) {
             let duration = info.started_instant.elapsed();
-            let ended_at = Local::now();
-            document.metadata.title = info.title.clone();
-            document.metadata.started_at = format_timestamp(info.started_at);
-            document.metadata.ended_at = Some(format_timestamp(ended_at));
-            document.metadata.duration_seconds = Some(duration.as_secs());
-            document.metadata.duration_formatted = Some(format_duration(duration));
-            self.write_session_document(&file_path, &document)?;
+            let metadata = SessionMetadata {
+                title: info.title.clone(),
+                started_at: format_timestamp(info.started_at),
+                ended_at: Some(format_timestamp(Local::now())),
+                duration_seconds: Some(duration.as_secs()),
+                duration_formatted: Some(format_duration(duration)),
+                summary_template: info.summary_template.clone(),
+                text_channel_id: info.text_channel,
+            };
+            // The session and its draft are only dropped once the final document is
+            // stored, so a failed write leaves the buffered lines in place for a retry.
+            if self.json_files {
+                self.update_document(&info.file_name, Some(&info), true, |document| {
+                    document.metadata = metadata.clone();
+                })?;
+            }
+            self.write_database("end session", |database| {
+                database.upsert_session(&info.record(guild_id, channel_id, &metadata))
+            })?;
+            self.sessions.remove(&(guild_id, channel_id));
+            // Lines appended while the final write was in flight are flushed here, or by
+            // the draft flusher if this write fails.
+            if self.json_files {
+                if let Err(err) = self.flush_draft(&info.file_name) {
+                    tracing::warn!(?err, file = %info.file_name, "Final draft flush failed");
+                } else {
+                    self.drafts
+                        .remove_if(&info.file_name, |_, draft| !draft.is_dirty());
+                }
+            }
             return Ok(Some(SessionSummary {
-                file_path,
+                file_name: info.file_name.clone(),
                 title: info.title.clone(),
                 started_at: info.started_at,
                 duration,
+                text_channel: info.text_channel,
             }));
         }
         Ok(None)
     }
 
+    pub fn active_session(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> Option<ActiveSession> {
+        self.sessions
+            .get(&(guild_id, channel_id))
+            .map(|entry| ActiveSession {
+                file_name: entry.file_name.clone(),
+                title: entry.title.clone(),
+                started_at: entry.started_at,
+                text_channel: entry.text_channel,
+            })
+    }
+
+    /// Changes the title of a running session and rewrites its metadata. The file name
+    /// keeps the slug it was created with. Returns `false` if no session is active.
+    pub fn retitle_session(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        title: Option<String>,
+    ) -> Result<bool> {
+        let clean_title = title
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        let Some(info) = self
+            .sessions
+            .get_mut(&(guild_id, channel_id))
+            .map(|mut entry| {
+                entry.title = clean_title.clone();
+                entry.value().clone()
+            })
+        else {
+            return Ok(false);
+        };
+        if self.json_files {
+            self.update_document(&info.file_name, Some(&info), true, |document| {
+                document.metadata.title = clean_title;
+            })?;
+        }
+        let metadata = info.initial_metadata();
+        self.write_database("retitle session", |database| {
+            database.upsert_session(&info.record(guild_id, channel_id, &metadata))
+        })?;
+        Ok(true)
+    }
+
+    /// Stores a structured summary in the session document (and database row). Returns
+    /// `false` for retention archives, which are left untouched.
+    pub fn store_summary(&self, file_name: &str, summary: &StructuredSummary) -> Result<bool> {
+        let Some((session_id, archived)) = session_id_from_file_name(file_name) else {
+            return Ok(false);
+        };
+        if archived {
+            return Ok(false);
+        }
+        if self.json_files {
+            let Some(mut document) = self.load_stored_document(file_name, None)? else {
+                return Ok(false);
+            };
+            document.summary = Some(summary.clone());
+            self.write_session_document(file_name, &document)?;
+        }
+        self.write_database("store summary", |database| {
+            database.set_summary(&session_id, summary)
+        })?;
+        Ok(true)
+    }
+
+    /// Writes the rendered markdown summary beside the session, sealed like session
+    /// documents when encryption is enabled.
+    pub fn store_summary_text(&self, session_id: &str, markdown: &str) -> Result<()> {
+        let mut bytes = markdown.as_bytes().to_vec();
+        if let Some(keyring) = &self.keyring {
+            bytes = keyring.seal(&bytes)?;
+        }
+        self.store.write(&summary_file_name(session_id), &bytes)
+    }
+
+    /// The summary stored by [`store_summary_text`](Self::store_summary_text), if any.
+    pub fn read_summary_text(&self, session_id: &str) -> Result<Option<String>> {
+        let Some(bytes) = self.store.read(&summary_file_name(session_id))? else {
+            return Ok(None);
+        };
+        let bytes = crypto::open_bytes(self.keyring.as_deref(), bytes)?;
+        Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// File names of sessions that are still being written to.
+    pub fn active_session_files(&self) -> HashSet<String> {
+        self.sessions
+            .iter()
+            .map(|entry| entry.value().file_name.clone())
+            .collect()
+    }
+
     fn session_file_name(&self, guild_id: GuildId, channel_id: ChannelId) -> String {
         let now = Local::now();
         match self.sessions.entry((guild_id, channel_id)) {
@@ -200,6 +495,8 @@ impl CaptionSink {
                 vacant.insert(SessionInfo {
                     file_name: file_name.clone(),
                     title: None,
+                    summary_template: None,
+                    text_channel: None,
                     started_at: now,
                     started_instant: Instant::now(),
                 });
@@ -263,14 +560,25 @@ impl CaptionSink {
         channel_id: ChannelId,
         entry: CaptionEntry,
     ) -> Result<()> {
-        let dir = &self.root;
-        fs::create_dir_all(dir)?;
         let file_name = self.session_file_name(guild_id, channel_id);
-        let file_path = dir.join(&file_name);
         let info = self.session_info_snapshot(guild_id, channel_id);
-        let mut document = self.load_session_document(&file_path, info.as_ref())?;
-        document.transcriptions.push(entry);
-        self.write_session_document(&file_path, &document)?;
+        if self.json_files {
+            self.update_document(&file_name, info.as_ref(), false, |document| {
+                document.transcriptions.push(entry.clone());
+            })?;
+        }
+        if let Some(info) = info.as_ref() {
+            let metadata = info.initial_metadata();
+            self.write_database("append caption", |database| {
+                database.append_entry(&info.record(guild_id, channel_id, &metadata), &entry)
+            })?;
+        }
+        // Nobody listening is the common case; a send error only means no subscribers.
+        let _ = self.events.send(CaptionEvent {
+            guild_id,
+            channel_id,
+            entry,
+        });
         Ok(())
     }
 
@@ -282,68 +590,270 @@ impl CaptionSink {
         new_id: UserId,
         new_name: &str,
     ) -> Result<bool> {
-        let dir = &self.root;
-        fs::create_dir_all(dir)?;
         let file_name = self.session_file_name(guild_id, channel_id);
-        let file_path = dir.join(&file_name);
-        if !file_path.exists() {
-            return Ok(false);
+        let mut database_updated = false;
+        if let Some(database) = &self.database {
+            let session_id = session_id_from_file_name(&file_name)
+                .map(|(id, _)| id)
+                .unwrap_or_default();
+            match database.relabel_placeholder(&session_id, placeholder, new_id, new_name) {
+                Ok(updated) => database_updated = updated,
+                Err(err) if self.json_files => {
+                    tracing::warn!(?err, "Transcript database relabel failed");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if !self.json_files {
+            return Ok(database_updated);
         }
 
-        let info = self.session_info_snapshot(guild_id, channel_id);
-        let mut document = self.load_session_document(&file_path, info.as_ref())?;
-
-        let mut updated = false;
-        for entry in &mut document.transcriptions {
-            if entry.speaker.id.is_none() && entry.speaker.name == placeholder {
-                entry.speaker.id = Some(new_id);
-                entry.speaker.name = new_name.to_string();
-                updated = true;
+        if self.drafts.contains_key(&file_name) {
+            let mut updated = false;
+            self.update_document(&file_name, None, false, |document| {
+                updated = relabel_entries(document, placeholder, new_id, new_name);
+            })?;
+            if updated {
+                self.flush_draft(&file_name)?;
             }
+            return Ok(updated || database_updated);
         }
 
+        let info = self.session_info_snapshot(guild_id, channel_id);
+        let Some(mut document) = self.load_stored_document(&file_name, info.as_ref())? else {
+            return Ok(database_updated);
+        };
+
+        let updated = relabel_entries(&mut document, placeholder, new_id, new_name);
         if updated {
-            self.write_session_document(&file_path, &document)?;
+            self.write_session_document(&file_name, &document)?;
         }
 
-        Ok(updated)
+        Ok(updated || database_updated)
+    }
+
+    /// Writes drafts whose flush interval has elapsed and drops clean drafts of sessions
+    /// that have ended. Called periodically by [`spawn_draft_flusher`] so buffered lines
+    /// reach the store even when a session goes quiet.
+    pub fn flush_due_drafts(&self) {
+        let interval = self.store.append_flush_interval();
+        let due: Vec<String> = self
+            .drafts
+            .iter()
+            .filter(|draft| draft.is_dirty() && draft.flushed_at.elapsed() >= interval)
+            .map(|draft| draft.key().clone())
+            .collect();
+        for file_name in due {
+            if let Err(err) = self.flush_draft(&file_name) {
+                tracing::warn!(?err, file = %file_name, "Caption draft flush failed");
+            }
+        }
+        let active = self.active_session_files();
+        self.drafts
+            .retain(|file_name, draft| draft.is_dirty() || active.contains(file_name));
+    }
+
+    /// Applies `edit` to an active session's document. When the store buffers appends the
+    /// document stays in memory and is written once per flush interval, or right away
+    /// with `flush`; otherwise every edit is a read and a write.
+    fn update_document(
+        &self,
+        file_name: &str,
+        info: Option<&SessionInfo>,
+        flush: bool,
+        edit: impl FnOnce(&mut SessionDocument),
+    ) -> Result<()> {
+        let interval = self.store.append_flush_interval();
+        if interval.is_zero() {
+            let mut document = self.load_session_document(file_name, info)?;
+            edit(&mut document);
+            return self.write_session_document(file_name, &document);
+        }
+        // Storage is never touched while a map guard is held: the document is loaded
+        // before the entry is taken and written from a snapshot in `flush_draft`.
+        let loaded = if self.drafts.contains_key(file_name) {
+            None
+        } else {
+            Some(self.load_session_document(file_name, info)?)
+        };
+        let due = {
+            let mut draft = match (self.drafts.entry(file_name.to_string()), loaded) {
+                (Entry::Occupied(entry), _) => entry.into_ref(),
+                (Entry::Vacant(entry), Some(document)) => {
+                    entry.insert(DraftDocument::new(document))
+                }
+                (Entry::Vacant(_), None) => {
+                    return Err(anyhow!("draft for {file_name} was dropped while editing"));
+                }
+            };
+            edit(&mut draft.document);
+            draft.revision += 1;
+            flush || draft.flushed_at.elapsed() >= interval
+        };
+        if due {
+            self.flush_draft(file_name)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a snapshot of the draft if it has unflushed edits.
+    fn flush_draft(&self, file_name: &str) -> Result<()> {
+        let Some(write_lock) = self
+            .drafts
+            .get(file_name)
+            .map(|draft| Arc::clone(&draft.write_lock))
+        else {
+            return Ok(());
+        };
+        let _writing = write_lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some((document, revision)) = self
+            .drafts
+            .get(file_name)
+            .filter(|draft| draft.is_dirty())
+            .map(|draft| (draft.document.clone(), draft.revision))
+        else {
+            return Ok(());
+        };
+        self.write_session_document(file_name, &document)?;
+        if let Some(mut draft) = self.drafts.get_mut(file_name) {
+            draft.flushed_revision = revision;
+            draft.flushed_at = Instant::now();
+        }
+        Ok(())
+    }
+
+    fn primary_database(&self) -> Option<&TranscriptDatabase> {
+        self.database.as_deref().filter(|_| !self.json_files)
+    }
+
+    /// Runs a database write. Failures only propagate when the database is the primary
+    /// store; mirrored writes are logged so JSON capture keeps going.
+    fn write_database(
+        &self,
+        action: &str,
+        write: impl FnOnce(&TranscriptDatabase) -> Result<()>,
+    ) -> Result<()> {
+        let Some(database) = self.database.as_deref() else {
+            return Ok(());
+        };
+        match write(database) {
+            Ok(()) => Ok(()),
+            Err(err) if self.json_files => {
+                tracing::warn!(?err, action, "Transcript database write failed");
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
     }
 
     fn load_session_document(
         &self,
-        path: &Path,
+        file_name: &str,
         info: Option<&SessionInfo>,
     ) -> Result<SessionDocument> {
-        if path.exists() {
-            let contents = fs::read_to_string(path)?;
-            if contents.trim().is_empty() {
-                return Ok(SessionDocument::new_with_info(info));
-            }
-            if let Ok(document) = serde_json::from_str::<SessionDocument>(&contents) {
-                return Ok(document);
-            }
-            if let Ok(entries) = serde_json::from_str::<Vec<CaptionEntry>>(&contents) {
-                let mut document = SessionDocument::new_with_info(info);
-                document.transcriptions = entries;
-                return Ok(document);
-            }
+        Ok(self
+            .load_stored_document(file_name, info)?
+            .unwrap_or_else(|| SessionDocument::new_with_info(info)))
+    }
+
+    /// `None` when nothing is stored under `file_name` yet.
+    fn load_stored_document(
+        &self,
+        file_name: &str,
+        info: Option<&SessionInfo>,
+    ) -> Result<Option<SessionDocument>> {
+        let Some(bytes) = self.store.read(file_name)? else {
+            return Ok(None);
+        };
+        let bytes = self.open_session_bytes(file_name, bytes)?;
+        let contents = String::from_utf8_lossy(&bytes);
+        if contents.trim().is_empty() {
+            return Ok(Some(SessionDocument::new_with_info(info)));
+        }
+        if let Ok(document) = serde_json::from_str::<SessionDocument>(&contents) {
+            return Ok(Some(document));
+        }
+        if let Ok(entries) = serde_json::from_str::<Vec<CaptionEntry>>(&contents) {
+            let mut document = SessionDocument::new_with_info(info);
+            document.transcriptions = entries;
+            return Ok(Some(document));
         }
-        Ok(SessionDocument::new_with_info(info))
+        Ok(Some(SessionDocument::new_with_info(info)))
     }
 
-    fn write_session_document(&self, path: &Path, document: &SessionDocument) -> Result<()> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+    fn write_session_document(&self, file_name: &str, document: &SessionDocument) -> Result<()> {
+        let mut bytes = serde_json::to_vec_pretty(document)?;
+        if let Some(keyring) = &self.keyring {
+            bytes = keyring.seal(&bytes)?;
         }
-        let file = fs::File::create(path)?;
-        serde_json::to_writer_pretty(BufWriter::new(file), document)?;
-        Ok(())
+        self.store.write(file_name, &bytes)
+    }
+}
+
+/// Periodically flushes buffered session documents, bounding how long a line can sit in
+/// memory to about one flush interval. `None` when the store writes every append directly.
+pub fn spawn_draft_flusher(sink: Arc<CaptionSink>) -> Option<tokio::task::JoinHandle<()>> {
+    let interval = sink.store().append_flush_interval();
+    if interval.is_zero() {
+        return None;
     }
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(DRAFT_FLUSH_TICK.min(interval));
+        loop {
+            ticker.tick().await;
+            let sink = Arc::clone(&sink);
+            if let Err(err) = tokio::task::spawn_blocking(move || sink.flush_due_drafts()).await {
+                tracing::error!("draft flush task join error: {err}");
+            }
+        }
+    }))
+}
+
+/// Gives lines captured under a placeholder speaker their resolved identity.
+fn relabel_entries(
+    document: &mut SessionDocument,
+    placeholder: &str,
+    new_id: UserId,
+    new_name: &str,
+) -> bool {
+    let mut updated = false;
+    for entry in &mut document.transcriptions {
+        if entry.speaker.id.is_none() && entry.speaker.name == placeholder {
+            entry.speaker.id = Some(new_id);
+            entry.speaker.name = new_name.to_string();
+            updated = true;
+        }
+    }
+    updated
 }
 
 impl SessionInfo {
     fn initial_metadata(&self) -> SessionMetadata {
-        SessionMetadata::new(self.title.clone(), self.started_at)
+        SessionMetadata {
+            summary_template: self.summary_template.clone(),
+            text_channel_id: self.text_channel,
+            ..SessionMetadata::new(self.title.clone(), self.started_at)
+        }
+    }
+
+    fn record<'a>(
+        &'a self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        metadata: &'a SessionMetadata,
+    ) -> SessionRecord<'a> {
+        SessionRecord {
+            id: self.session_id(),
+            guild_id,
+            channel_id,
+            metadata,
+        }
+    }
+
+    fn session_id(&self) -> &str {
+        self.file_name
+            .strip_suffix(".json")
+            .unwrap_or(&self.file_name)
     }
 }
 
@@ -355,6 +865,8 @@ impl SessionMetadata {
             ended_at: None,
             duration_seconds: None,
             duration_formatted: None,
+            summary_template: None,
+            text_channel_id: None,
         }
     }
 }
@@ -364,7 +876,36 @@ impl SessionDocument {
         Self {
             metadata: info.initial_metadata(),
             transcriptions: Vec::new(),
+            summary: None,
+        }
+    }
+
+    /// Plain-text rendering used for `.txt` exports: a short header followed by one
+    /// `[timestamp] speaker: comment` line per entry.
+    pub fn render_text(&self) -> String {
+        let mut buffer = String::new();
+        if let Some(title) = self.metadata.title.as_deref() {
+            buffer.push_str(&format!("Session Title: {title}\n"));
+        }
+        buffer.push_str(&format!("Started At: {}\n", self.metadata.started_at));
+        if let Some(ended) = self.metadata.ended_at.as_deref() {
+            buffer.push_str(&format!("Ended At: {ended}\n"));
+        }
+        if let Some(duration) = self.metadata.duration_formatted.as_deref() {
+            buffer.push_str(&format!("Duration: {duration}\n"));
+        }
+        buffer.push('\n');
+        for entry in &self.transcriptions {
+            let comment = entry.comment.trim();
+            if comment.is_empty() {
+                continue;
+            }
+            buffer.push_str(&format!(
+                "[{}] {}: {}\n",
+                entry.timestamp, entry.speaker.name, comment
+            ));
         }
+        buffer
     }
 
     fn new_with_info(info: Option<&SessionInfo>) -> Self {
@@ -373,6 +914,7 @@ impl SessionDocument {
             None => Self {
                 metadata: SessionMetadata::new(None, Local::now()),
                 transcriptions: Vec::new(),
+                summary: None,
             },
         }
     }
@@ -382,7 +924,7 @@ fn format_timestamp(value: DateTime<Local>) -> String {
     value.to_rfc3339_opts(SecondsFormat::Secs, true)
 }
 
-fn format_duration(duration: Duration) -> String {
+pub(super) fn format_duration(duration: Duration) -> String {
     let total_secs = duration.as_secs();
     let hours = total_secs / 3600;
     let minutes = (total_secs % 3600) / 60;
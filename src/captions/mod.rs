@@ -1,3 +1,10 @@
+pub mod crypto;
+pub mod database;
+pub mod index;
 pub mod json;
+pub mod storage;
 
-pub use json::{CaptionEntry, CaptionSink, SessionSummary, SpeakerInfo};
+pub use json::{
+    ActiveSession, CaptionEntry, CaptionEvent, CaptionSink, SessionDocument, SessionSummary,
+    SpeakerInfo, spawn_draft_flusher,
+};
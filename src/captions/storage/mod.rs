@@ -0,0 +1,70 @@
+mod local;
+mod s3;
+
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+
+pub use local::LocalStore;
+pub use s3::{S3Config, S3Store};
+
+const WRITE_PROBE_NAME: &str = ".hammock-readyz";
+
+/// Where caption session documents live. Names are flat file names such as
+/// `<guild>_<channel>_<timestamp>.json`; backends map them onto paths or object keys.
+///
+/// Methods are blocking. Call them from `spawn_blocking` (or other blocking contexts)
+/// when they sit on a hot async path.
+pub trait SessionStore: Send + Sync + Debug {
+    /// Human-readable location for logs, e.g. `captions/` or `s3://bucket/prefix/`.
+    fn location(&self) -> String;
+
+    /// Returns `None` when no object with this name exists.
+    fn read(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    fn write(&self, name: &str, bytes: &[u8]) -> Result<()>;
+
+    /// Deleting a missing object is not an error.
+    fn delete(&self, name: &str) -> Result<()>;
+
+    fn list(&self) -> Result<Vec<StoredObject>>;
+
+    /// How long new caption lines may wait in memory before an active session's document
+    /// is rewritten. Zero rewrites it on every line.
+    fn append_flush_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Writes and removes a small probe object; used by `/k8s/readyz`.
+    fn check_writable(&self) -> Result<()> {
+        self.write(WRITE_PROBE_NAME, b"ok")?;
+        self.delete(WRITE_PROBE_NAME)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub name: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+#[derive(Clone, Debug)]
+pub enum StorageConfig {
+    Local { root: PathBuf },
+    S3(S3Config),
+}
+
+impl StorageConfig {
+    pub fn open(&self) -> Result<Arc<dyn SessionStore>> {
+        Ok(match self {
+            Self::Local { root } => Arc::new(LocalStore::new(root.clone())),
+            Self::S3(config) => Arc::new(S3Store::new(config.clone())?),
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use chrono::DateTime;
+use poise::serenity_prelude as serenity;
+use serenity::model::prelude::User;
+
+use crate::{
+    BotContext, Error,
+    captions::index::{RetrievalQuery, RetrievedLine},
+    commands::lookback_start,
+    redaction::Redactor,
+    utils::truncate,
+};
+
+/// Best-matching caption lines sent to the provider, each with its neighbours.
+const MAX_MATCHES: usize = 25;
+const CONTEXT_LINES: usize = 2;
+const MAX_ANSWER_CHARS: usize = 1400;
+const MAX_SOURCE_CHARS: usize = 120;
+const MESSAGE_LIMIT: usize = 2000;
+
+/// Ask a question about a recorded session or this server's caption history.
+#[poise::command(slash_command, guild_only)]
+pub async fn ask(
+    ctx: BotContext<'_>,
+    #[description = "What you want to know, e.g. what did Sam say about the release date?"]
+    question: String,
+    #[description = "Session id from /sessions list (defaults to every session in this server)"]
+    session: Option<String>,
+    #[description = "Only consider lines spoken by this member"] speaker: Option<User>,
+    #[description = "Only consider sessions from the last N days"]
+    #[min = 1]
+    #[max = 3650]
+    days: Option<u32>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let state = Arc::clone(ctx.data());
+    let Some(summarizer) = state.summarizer() else {
+        ctx.say("Questions need a summary provider, and none is configured on this bot")
+            .await?;
+        return Ok(());
+    };
+    if question.trim().is_empty() {
+        ctx.say("Ask a question first").await?;
+        return Ok(());
+    }
+
+    let since = match days.map(lookback_start) {
+        Some(None) => {
+            ctx.say("`days` must be between 1 and 3650").await?;
+            return Ok(());
+        }
+        since => since.flatten(),
+    };
+
+    ctx.defer().await?;
+
+    let session_id = session
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    let query = RetrievalQuery {
+        guild_id: Some(guild_id),
+        session_id: session_id.clone(),
+        speaker_id: speaker.as_ref().map(|user| user.id),
+        since,
+        question: question.clone(),
+        limit: MAX_MATCHES,
+        context: CONTEXT_LINES,
+    };
+    let index = state.session_index();
+    let (known_session, lines) = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        let known = query.session_id.as_deref().is_none_or(|id| {
+            index
+                .get(id)
+                .is_some_and(|found| found.guild_id == guild_id)
+        });
+        Ok::<_, anyhow::Error>((known, index.retrieve(&query)))
+    })
+    .await??;
+
+    if !known_session {
+        ctx.say(format!(
+            "No session `{}` exists in this server",
+            session_id.unwrap_or_default()
+        ))
+        .await?;
+        return Ok(());
+    }
+    if lines.is_empty() {
+        ctx.say("No caption lines look relevant to that question")
+            .await?;
+        return Ok(());
+    }
+
+    match summarizer.answer(&question, &lines).await {
+        Ok(answer) => {
+            let sources: Vec<(usize, &RetrievedLine)> = answer
+                .citations
+                .iter()
+                .map(|index| (index + 1, &lines[*index]))
+                .collect();
+            let content = render_answer(&question, &answer.text, &sources, &state.redactor);
+            ctx.say(content).await?;
+        }
+        Err(err) => {
+            tracing::error!(
+                ?err,
+                provider = summarizer.provider(),
+                "Question answering failed"
+            );
+            ctx.say("The question could not be answered; try again later")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The answer followed by its cited lines, numbered as the model cited them.
+fn render_answer(
+    question: &str,
+    answer: &str,
+    sources: &[(usize, &RetrievedLine)],
+    redactor: &Redactor,
+) -> String {
+    let mut content = format!(
+        "**Q:** {}\n{}\n",
+        truncate(question.trim(), 200),
+        truncate(answer.trim(), MAX_ANSWER_CHARS)
+    );
+    if sources.is_empty() {
+        return content;
+    }
+    content.push_str("\n**Sources**\n");
+    for (number, line) in sources {
+        let when = DateTime::parse_from_rfc3339(&line.timestamp)
+            .map(|at| format!("<t:{}:f>", at.timestamp()))
+            .unwrap_or_else(|_| format!("`{}`", line.timestamp));
+        let entry = format!(
+            "`[{number}]` **{}** {when} in `{}`: {}\n",
+            line.speaker,
+            line.session_id,
+            truncate(&redactor.export_text(line.comment.trim()), MAX_SOURCE_CHARS)
+        );
+        if content.chars().count() + entry.chars().count() > MESSAGE_LIMIT - 20 {
+            content.push_str("…more sources omitted");
+            break;
+        }
+        content.push_str(&entry);
+    }
+    content
+}
@@ -0,0 +1,182 @@
+pub mod ask;
+pub mod search;
+pub mod sessions;
+pub mod summarize;
+
+pub use ask::ask;
+pub use search::search;
+pub use sessions::sessions;
+pub use summarize::summarize;
+
+use std::{future::Future, sync::Arc};
+
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use poise::{
+    CreateReply,
+    serenity_prelude::{self as serenity, CreateMessage},
+};
+use serenity::model::{id::ChannelId, prelude::Mentionable};
+use tokio::sync::mpsc;
+
+use crate::{
+    BotContext,
+    captions::{CaptionSink, index::session_id_from_file_name},
+    summaries::{
+        JobStatus, SummaryOutput, SummaryProgress, SummaryQueue, SummaryRequest,
+        TranscriptSummarizer,
+    },
+};
+
+/// Start of a `days:` lookback window; `None` when it falls outside the calendar.
+/// Commands also cap the option at 3650 days.
+pub fn lookback_start(days: u32) -> Option<DateTime<FixedOffset>> {
+    let window = TimeDelta::try_days(i64::from(days))?;
+    Utc::now()
+        .checked_sub_signed(window)
+        .map(|since| since.fixed_offset())
+}
+
+/// Summarizes a transcript, posting a progress message that is edited as chunks of a
+/// long session complete. Short sessions produce no progress message.
+pub async fn summarize_with_progress(
+    ctx: BotContext<'_>,
+    summarizer: &TranscriptSummarizer,
+    transcript: &[u8],
+    request: &SummaryRequest<'_>,
+) -> anyhow::Result<SummaryOutput> {
+    with_summary_progress(ctx, |progress| {
+        summarizer.summarize_with_progress(transcript, request, Some(progress))
+    })
+    .await
+}
+
+/// Runs `summarize` while relaying the progress it reports to the channel.
+pub async fn with_summary_progress<F, Fut, T>(ctx: BotContext<'_>, summarize: F) -> T
+where
+    F: FnOnce(mpsc::UnboundedSender<SummaryProgress>) -> Fut,
+    Fut: Future<Output = T>,
+{
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+    let summary = summarize(progress_tx);
+    let updates = async {
+        let mut message = None;
+        while let Some(update) = progress_rx.recv().await {
+            let content = progress_text(update);
+            let result = match message.as_ref() {
+                Some(handle) => {
+                    poise::ReplyHandle::edit(handle, ctx, CreateReply::default().content(content))
+                        .await
+                        .map(|_| None)
+                }
+                None => ctx.say(content).await.map(Some),
+            };
+            match result {
+                Ok(Some(handle)) => message = Some(handle),
+                Ok(None) => {}
+                Err(err) => tracing::warn!(?err, "Failed to post summary progress"),
+            }
+        }
+    };
+    let (summary, ()) = tokio::join!(summary, updates);
+    summary
+}
+
+/// Status line for a long summary, edited in place as it advances.
+pub fn progress_text(update: SummaryProgress) -> String {
+    match update {
+        SummaryProgress::Chunk { done, total } => {
+            format!("Summarizing long session: {done}/{total} parts done…")
+        }
+        SummaryProgress::Reducing => "Summarizing long session: writing final notes…".to_string(),
+    }
+}
+
+/// Chat reply for a finished summary: an embed for structured summaries, plain markdown
+/// otherwise.
+pub fn summary_reply(label: &str, summary: &SummaryOutput) -> CreateReply {
+    match summary {
+        SummaryOutput::Markdown(text) => {
+            CreateReply::default().content(format!("Summary for {label}:\n{text}"))
+        }
+        SummaryOutput::Structured(structured) => {
+            CreateReply::default().embed(structured.to_embed(label))
+        }
+    }
+}
+
+/// Channel message for a summary posted outside an interaction, such as a retried job.
+pub fn summary_message(label: &str, summary: &SummaryOutput) -> CreateMessage {
+    match summary {
+        SummaryOutput::Markdown(text) => {
+            CreateMessage::new().content(format!("Summary for {label}:\n{text}"))
+        }
+        SummaryOutput::Structured(structured) => {
+            CreateMessage::new().embed(structured.to_embed(label))
+        }
+    }
+}
+
+/// Saves a summary next to its session: the markdown rendering as
+/// `<session id>.summary.md`, plus the structured form in the session document. Failures
+/// are logged; the summary has already been posted.
+pub async fn store_summary(sink: Arc<CaptionSink>, file_name: String, summary: &SummaryOutput) {
+    let Some((session_id, _)) = session_id_from_file_name(&file_name) else {
+        return;
+    };
+    let markdown = summary.to_markdown();
+    let structured = summary.structured().cloned();
+    let result = tokio::task::spawn_blocking(move || {
+        sink.store_summary_text(&session_id, &markdown)?;
+        if let Some(structured) = structured {
+            sink.store_summary(&file_name, &structured)?;
+        }
+        Ok::<_, anyhow::Error>(())
+    })
+    .await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => tracing::warn!(?err, "Failed to store summary"),
+        Err(err) => tracing::warn!(?err, "Summary store task failed"),
+    }
+}
+
+/// What to tell the user after a queued summary attempt failed.
+pub fn summary_failure_message(
+    queue: &SummaryQueue,
+    session_id: &str,
+    channel: ChannelId,
+) -> String {
+    match queue.get(session_id) {
+        Some(job) if job.status == JobStatus::Pending => format!(
+            "The summary request failed; it will be retried automatically and posted in {} once it succeeds",
+            channel.mention()
+        ),
+        _ => format!(
+            "The summary request failed; use `/sessions summary id:{session_id} retry:true` to try again"
+        ),
+    }
+}
+
+/// Suggests summary templates available in the current guild.
+pub async fn autocomplete_template(
+    ctx: BotContext<'_>,
+    partial: &str,
+) -> Vec<serenity::AutocompleteChoice> {
+    let (Some(guild_id), Some(summarizer)) = (ctx.guild_id(), ctx.data().summarizer()) else {
+        return Vec::new();
+    };
+    let partial = partial.trim().to_ascii_lowercase();
+    summarizer
+        .templates()
+        .available(guild_id)
+        .filter(|(name, _)| name.contains(&partial))
+        .map(|(name, template)| {
+            let label = match template.description.as_deref() {
+                Some(description) => format!("{name} — {description}"),
+                None => name.to_string(),
+            };
+            serenity::AutocompleteChoice::new(label.chars().take(100).collect::<String>(), name)
+        })
+        .take(25)
+        .collect()
+}
@@ -0,0 +1,113 @@
+use poise::{CreateReply, serenity_prelude as serenity};
+use serenity::model::{
+    id::ChannelId,
+    prelude::{Mentionable, User},
+};
+
+use crate::{
+    BotContext, Error,
+    captions::index::{SearchHit, SearchQuery},
+    commands::lookback_start,
+    utils::truncate,
+};
+
+const MAX_RESULTS: usize = 10;
+const MAX_SNIPPET_CHARS: usize = 160;
+
+/// Search past caption sessions in this server.
+#[poise::command(slash_command, guild_only)]
+pub async fn search(
+    ctx: BotContext<'_>,
+    #[description = "Words that must appear in the caption line"] query: String,
+    #[description = "Only search sessions recorded in this voice channel"] channel: Option<
+        ChannelId,
+    >,
+    #[description = "Only return lines spoken by this member"] speaker: Option<User>,
+    #[description = "Only search sessions from the last N days"]
+    #[min = 1]
+    #[max = 3650]
+    days: Option<u32>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    let since = match days.map(lookback_start) {
+        Some(None) => {
+            ctx.send(
+                CreateReply::default()
+                    .content("`days` must be between 1 and 3650")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        }
+        since => since.flatten(),
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let search_query = SearchQuery {
+        guild_id: Some(guild_id),
+        text: query.clone(),
+        channel_id: channel,
+        speaker_id: speaker.as_ref().map(|user| user.id),
+        since,
+        until: None,
+        limit: MAX_RESULTS,
+    };
+
+    let index = ctx.data().session_index();
+    let hits = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        Ok::<_, anyhow::Error>(index.search(&search_query))
+    })
+    .await??;
+
+    let content = if hits.is_empty() {
+        format!("No caption lines matched \"{}\"", query.trim())
+    } else {
+        render_hits(&query, &hits)
+    };
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+fn render_hits(query: &str, hits: &[SearchHit]) -> String {
+    let mut content = format!("Results for \"{}\":\n", query.trim());
+    for hit in hits {
+        let channel = hit
+            .channel_id
+            .parse::<u64>()
+            .map(|id| ChannelId::new(id).mention().to_string())
+            .unwrap_or_else(|_| hit.channel_id.clone());
+        let title = hit
+            .session_title
+            .as_deref()
+            .map(|title| format!(" \"{title}\""))
+            .unwrap_or_default();
+        // Angle brackets keep Discord from unfurling a preview of the API link.
+        let open = match hit.session_url.as_deref() {
+            Some(url) => format!("<{url}>"),
+            None => format!("`/sessions get id:{}`", hit.session_id),
+        };
+        let line = format!(
+            "- `{}` **{}** in {}{}: {}\n  session `{}` entry {} · {}\n",
+            hit.timestamp,
+            hit.speaker,
+            channel,
+            title,
+            truncate(&hit.comment, MAX_SNIPPET_CHARS),
+            hit.session_id,
+            hit.entry_index,
+            open
+        );
+        if content.len() + line.len() > 1900 {
+            content.push_str("…more results omitted");
+            break;
+        }
+        content.push_str(&line);
+    }
+    content
+}
@@ -0,0 +1,421 @@
+use std::sync::Arc;
+
+use poise::{
+    CreateReply,
+    serenity_prelude::{self as serenity, CreateAttachment},
+};
+use serenity::model::{
+    id::{ChannelId, GuildId},
+    prelude::Mentionable,
+};
+
+use crate::{
+    BotContext, BotState, Error,
+    captions::index::{IndexedSession, parse_date_bound},
+    summaries::{JobStatus, SummaryJob, SummaryOutput, SummaryRequest},
+};
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum SessionFormat {
+    #[name = "JSON transcript"]
+    Json,
+    #[name = "Plain text transcript"]
+    Text,
+    #[name = "AI summary"]
+    Summary,
+}
+
+/// Browse and re-download caption sessions recorded in this server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("list", "get", "summary"),
+    subcommand_required
+)]
+pub async fn sessions(_ctx: BotContext<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List recorded sessions, newest first.
+#[poise::command(slash_command, guild_only)]
+async fn list(
+    ctx: BotContext<'_>,
+    #[description = "Only sessions recorded in this voice channel"] channel: Option<ChannelId>,
+    #[description = "Only sessions started on or after this date (YYYY-MM-DD)"] since: Option<
+        String,
+    >,
+    #[description = "Only sessions started on or before this date (YYYY-MM-DD)"] until: Option<
+        String,
+    >,
+    #[description = "Only sessions whose title contains this text"] title: Option<String>,
+    #[description = "Page number"]
+    #[min = 1]
+    page: Option<u32>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let since_bound = match since.as_deref().map(|raw| parse_date_bound(raw, false)) {
+        Some(None) => {
+            ctx.say("`since` must look like 2024-01-31").await?;
+            return Ok(());
+        }
+        other => other.flatten(),
+    };
+    let until_bound = match until.as_deref().map(|raw| parse_date_bound(raw, true)) {
+        Some(None) => {
+            ctx.say("`until` must look like 2024-01-31").await?;
+            return Ok(());
+        }
+        other => other.flatten(),
+    };
+    let title_filter = title
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty());
+
+    let index = ctx.data().session_index();
+    let sessions = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        Ok::<_, anyhow::Error>(index.sessions_for_guild(guild_id))
+    })
+    .await??;
+
+    let matching: Vec<IndexedSession> = sessions
+        .into_iter()
+        .filter(|session| channel.is_none_or(|channel| session.channel_id == channel))
+        .filter(|session| match (since_bound, session.started_at) {
+            (Some(since), Some(started)) => started >= since,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .filter(|session| match (until_bound, session.started_at) {
+            (Some(until), Some(started)) => started <= until,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .filter(|session| {
+            title_filter.as_deref().is_none_or(|needle| {
+                session
+                    .title
+                    .as_deref()
+                    .is_some_and(|title| title.to_lowercase().contains(needle))
+            })
+        })
+        .collect();
+
+    if matching.is_empty() {
+        ctx.say("No sessions matched those filters").await?;
+        return Ok(());
+    }
+
+    let pages = matching.len().div_ceil(PAGE_SIZE);
+    let page = (page.unwrap_or(1) as usize).clamp(1, pages);
+    let mut content = format!(
+        "Sessions {}–{} of {} (page {page}/{pages}):\n",
+        (page - 1) * PAGE_SIZE + 1,
+        (page * PAGE_SIZE).min(matching.len()),
+        matching.len()
+    );
+    for session in matching.iter().skip((page - 1) * PAGE_SIZE).take(PAGE_SIZE) {
+        content.push_str(&describe_session(session));
+        content.push('\n');
+    }
+    if page < pages {
+        content.push_str(&format!("Use `page: {}` for more.", page + 1));
+    }
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+/// Re-upload a recorded session as a transcript or summary.
+#[poise::command(slash_command, guild_only)]
+async fn get(
+    ctx: BotContext<'_>,
+    #[description = "Session id from /sessions list"] id: String,
+    #[description = "What to upload (defaults to the JSON transcript)"] format: Option<
+        SessionFormat,
+    >,
+    #[description = "Summary template (defaults to the one chosen at /join)"]
+    #[autocomplete = "super::autocomplete_template"]
+    template: Option<String>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    ctx.defer().await?;
+
+    let state = Arc::clone(ctx.data());
+    let Some(session) = find_session(&state, guild_id, &id).await? else {
+        ctx.say(format!("No session `{}` exists in this server", id.trim()))
+            .await?;
+        return Ok(());
+    };
+
+    let sink = Arc::clone(&state.caption_sink);
+    let file_name = session.file_name.clone();
+    let document =
+        tokio::task::spawn_blocking(move || sink.read_session_document(&file_name)).await?;
+    let mut document = match document {
+        Ok(document) => document,
+        Err(err) => {
+            tracing::error!(?err, session = %session.id, "Failed to load session for re-upload");
+            ctx.say("Failed to read that session from storage").await?;
+            return Ok(());
+        }
+    };
+    state.redactor.redact_document_for_export(&mut document);
+
+    let label = describe_session(&session);
+    match format.unwrap_or(SessionFormat::Json) {
+        SessionFormat::Json => {
+            let bytes = serde_json::to_vec(&document)?;
+            ctx.send(
+                CreateReply::default()
+                    .content(label)
+                    .attachment(CreateAttachment::bytes(
+                        bytes,
+                        format!("{}.json", session.id),
+                    )),
+            )
+            .await?;
+        }
+        SessionFormat::Text => {
+            let text = document.render_text();
+            ctx.send(
+                CreateReply::default()
+                    .content(label)
+                    .attachment(CreateAttachment::bytes(
+                        text.into_bytes(),
+                        format!("{}.txt", session.id),
+                    )),
+            )
+            .await?;
+        }
+        SessionFormat::Summary => {
+            let Some(summarizer) = state.summarizer() else {
+                ctx.say("Summaries are disabled on this bot (no summary provider configured)")
+                    .await?;
+                return Ok(());
+            };
+            let bytes = serde_json::to_vec(&document)?;
+            let summary_label = session.title.clone().unwrap_or_else(|| session.id.clone());
+            let request = SummaryRequest {
+                label: &summary_label,
+                guild_id: Some(guild_id),
+                template: template.as_deref(),
+            };
+            match super::summarize_with_progress(ctx, &summarizer, &bytes, &request).await {
+                Ok(output) => {
+                    ctx.send(super::summary_reply(&summary_label, &output))
+                        .await?;
+                    super::store_summary(
+                        Arc::clone(&state.caption_sink),
+                        session.file_name.clone(),
+                        &output,
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    tracing::error!(?err, session = %session.id, "On-demand summary failed");
+                    ctx.say("The summary request failed; try again later")
+                        .await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Repost the stored summary of a session to its original channel, or summarize it again.
+#[poise::command(slash_command, guild_only)]
+async fn summary(
+    ctx: BotContext<'_>,
+    #[description = "Session id from /sessions list"] id: String,
+    #[description = "Summarize the session now, even if earlier attempts failed"] retry: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+
+    ctx.defer().await?;
+
+    let state = Arc::clone(ctx.data());
+    let Some(queue) = state.summary_queue() else {
+        ctx.say("Summaries are disabled on this bot (no summary provider configured)")
+            .await?;
+        return Ok(());
+    };
+    let Some(session) = find_session(&state, guild_id, &id).await? else {
+        ctx.say(format!("No session `{}` exists in this server", id.trim()))
+            .await?;
+        return Ok(());
+    };
+
+    let existing = queue.get(&session.id);
+    let channel_id = existing
+        .as_ref()
+        .map(|job| job.channel_id)
+        .unwrap_or_else(|| ctx.channel_id());
+    let label = existing
+        .as_ref()
+        .map(|job| job.label.clone())
+        .unwrap_or_else(|| session.title.clone().unwrap_or_else(|| session.id.clone()));
+
+    if !retry.unwrap_or(false) {
+        let sink = Arc::clone(&state.caption_sink);
+        let (file_name, session_id) = (session.file_name.clone(), session.id.clone());
+        let stored = tokio::task::spawn_blocking(move || {
+            let structured = sink.read_session_document(&file_name)?.summary;
+            Ok::<_, anyhow::Error>(match structured {
+                Some(structured) => Some(SummaryOutput::Structured(structured)),
+                None => sink
+                    .read_summary_text(&session_id)?
+                    .map(SummaryOutput::Markdown),
+            })
+        })
+        .await?;
+        match stored {
+            Ok(Some(output)) => {
+                post_summary(ctx, channel_id, &label, &output).await?;
+            }
+            Ok(None) => {
+                let status = existing
+                    .as_ref()
+                    .map(describe_job)
+                    .unwrap_or_else(|| "No summary has been produced for this session".to_string());
+                ctx.say(format!("{status}. Use `retry: true` to summarize it now."))
+                    .await?;
+            }
+            Err(err) => {
+                tracing::error!(?err, session = %session.id, "Failed to load stored summary");
+                ctx.say("Failed to read that session's summary from storage")
+                    .await?;
+            }
+        }
+        return Ok(());
+    }
+
+    let job = SummaryJob::new(
+        session.id.clone(),
+        session.file_name.clone(),
+        guild_id,
+        channel_id,
+        label.clone(),
+        session.title.clone(),
+    );
+    let Some(job) = queue.enqueue(job) else {
+        ctx.say("A summary of that session is already being generated")
+            .await?;
+        return Ok(());
+    };
+    let result =
+        super::with_summary_progress(ctx, |progress| state.run_summary_job(&job, Some(progress)))
+            .await;
+    match result {
+        Ok(output) => post_summary(ctx, channel_id, &label, &output).await?,
+        Err(_) => {
+            ctx.say(super::summary_failure_message(
+                &queue,
+                &job.session_id,
+                channel_id,
+            ))
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn find_session(
+    state: &Arc<BotState>,
+    guild_id: GuildId,
+    id: &str,
+) -> anyhow::Result<Option<IndexedSession>> {
+    let index = state.session_index();
+    let lookup_id = id.trim().to_string();
+    let session = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        Ok::<_, anyhow::Error>(index.get(&lookup_id))
+    })
+    .await??;
+    Ok(session.filter(|session| session.guild_id == guild_id))
+}
+
+/// Replies in place when `channel_id` is the current channel; otherwise posts there and
+/// confirms.
+async fn post_summary(
+    ctx: BotContext<'_>,
+    channel_id: ChannelId,
+    label: &str,
+    output: &SummaryOutput,
+) -> Result<(), Error> {
+    if channel_id == ctx.channel_id() {
+        ctx.send(super::summary_reply(label, output)).await?;
+        return Ok(());
+    }
+    channel_id
+        .send_message(ctx.http(), super::summary_message(label, output))
+        .await?;
+    ctx.say(format!("Posted the summary in {}", channel_id.mention()))
+        .await?;
+    Ok(())
+}
+
+fn describe_job(job: &SummaryJob) -> String {
+    let last_error = job
+        .last_error
+        .as_deref()
+        .map(|error| {
+            format!(
+                " (last error: {})",
+                error.chars().take(300).collect::<String>()
+            )
+        })
+        .unwrap_or_default();
+    match job.status {
+        JobStatus::Pending if job.attempts == 0 => "The summary is queued".to_string(),
+        JobStatus::Pending => format!(
+            "The summary has failed {} time(s) and will be retried <t:{}:R>{last_error}",
+            job.attempts,
+            job.next_attempt_at.timestamp()
+        ),
+        JobStatus::Failed => format!(
+            "The summary failed after {} attempts{last_error}",
+            job.attempts
+        ),
+        JobStatus::Done => "The summary was generated but is no longer stored".to_string(),
+    }
+}
+
+fn describe_session(session: &IndexedSession) -> String {
+    let started = session
+        .started_at
+        .map(|value| value.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown start".to_string());
+    let title = session.title.as_deref().unwrap_or("Untitled");
+    let duration = session
+        .duration_formatted
+        .as_deref()
+        .map(|duration| format!(" ({duration})"))
+        .unwrap_or_default();
+    let archived = if session.archived { " [archived]" } else { "" };
+    format!(
+        "`{}` — **{}** in {} on {}{}{}, {} lines",
+        session.id,
+        title,
+        session.channel_id.mention(),
+        started,
+        duration,
+        archived,
+        session.lines.len()
+    )
+}
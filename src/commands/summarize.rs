@@ -0,0 +1,347 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration as ChronoDuration, FixedOffset, Local};
+use serenity::model::id::UserId;
+
+use crate::{
+    BotContext, Error,
+    captions::{CaptionEntry, SessionDocument},
+    summaries::SummaryRequest,
+};
+
+/// Longest `from`/`to` offset accepted, well past any real session.
+const MAX_OFFSET_HOURS: i64 = 48;
+
+/// Summarize the session being recorded so far, or a past session.
+#[poise::command(slash_command, guild_only)]
+pub async fn summarize(
+    ctx: BotContext<'_>,
+    #[description = "Session id from /sessions list (defaults to the session being recorded)"]
+    session: Option<String>,
+    #[description = "Only the last N minutes of the session"]
+    #[min = 1]
+    #[max = 2880]
+    last_minutes: Option<u32>,
+    #[description = "Start offset into the session: minutes (45) or h:mm (1:15)"] from: Option<
+        String,
+    >,
+    #[description = "End offset into the session: minutes (90) or h:mm (1:30)"] to: Option<String>,
+    #[description = "Only these speakers: mentions or names, separated by commas"] speakers: Option<
+        String,
+    >,
+    #[description = "Summary template"]
+    #[autocomplete = "super::autocomplete_template"]
+    template: Option<String>,
+) -> Result<(), Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let state = Arc::clone(ctx.data());
+    let Some(summarizer) = state.summarizer() else {
+        ctx.say("Summaries are disabled on this bot (no summary provider configured)")
+            .await?;
+        return Ok(());
+    };
+
+    ctx.defer().await?;
+
+    let range = match TimeRange::from_options(last_minutes, from.as_deref(), to.as_deref()) {
+        Ok(range) => range,
+        Err(message) => {
+            ctx.say(message).await?;
+            return Ok(());
+        }
+    };
+    let speakers = speakers
+        .as_deref()
+        .map(SpeakerFilter::parse)
+        .filter(|filter| !filter.is_empty());
+
+    let active = state.active_session(guild_id);
+    let (file_name, title, session_id) = match session.as_deref().map(str::trim) {
+        Some(id) if !id.is_empty() => {
+            let index = state.session_index();
+            let lookup_id = id.to_string();
+            let found = tokio::task::spawn_blocking(move || {
+                index.refresh()?;
+                Ok::<_, anyhow::Error>(index.get(&lookup_id))
+            })
+            .await??
+            .filter(|session| session.guild_id == guild_id);
+            let Some(found) = found else {
+                ctx.say(format!("No session `{id}` exists in this server"))
+                    .await?;
+                return Ok(());
+            };
+            (found.file_name, found.title, found.id)
+        }
+        _ => {
+            let Some(active) = active.as_ref() else {
+                ctx.say("Nothing is being recorded here; pass a session id from /sessions list")
+                    .await?;
+                return Ok(());
+            };
+            let id = active
+                .file_name
+                .strip_suffix(".json")
+                .unwrap_or(&active.file_name)
+                .to_string();
+            (active.file_name.clone(), active.title.clone(), id)
+        }
+    };
+    let in_progress = active
+        .as_ref()
+        .is_some_and(|active| active.file_name == file_name);
+
+    let sink = Arc::clone(&state.caption_sink);
+    let read_name = file_name.clone();
+    let document =
+        tokio::task::spawn_blocking(move || sink.read_session_document(&read_name)).await?;
+    let mut document = match document {
+        Ok(document) => document,
+        Err(err) => {
+            tracing::error!(?err, session = %session_id, "Failed to load session for summary");
+            ctx.say("Failed to read that session from storage").await?;
+            return Ok(());
+        }
+    };
+
+    let total_lines = document.transcriptions.len();
+    let now = in_progress.then(|| Local::now().fixed_offset());
+    filter_entries(&mut document, &range, speakers.as_ref(), now);
+    if document.transcriptions.is_empty() {
+        let message = if total_lines == 0 {
+            "That session has no captions yet"
+        } else {
+            "No caption lines match that time range and speaker selection"
+        };
+        ctx.say(message).await?;
+        return Ok(());
+    }
+    state.redactor.redact_document_for_export(&mut document);
+
+    let mut scope = Vec::new();
+    if in_progress {
+        scope.push("so far".to_string());
+    }
+    if let Some(description) = range.describe() {
+        scope.push(description);
+    }
+    if let Some(speakers) = speakers.as_ref() {
+        scope.push(speakers.describe());
+    }
+    let base = title.clone().unwrap_or_else(|| session_id.clone());
+    let label = if scope.is_empty() {
+        base
+    } else {
+        format!("{base} ({})", scope.join(", "))
+    };
+
+    let bytes = serde_json::to_vec(&document)?;
+    let request = SummaryRequest {
+        label: &label,
+        guild_id: Some(guild_id),
+        template: template.as_deref(),
+    };
+    match super::summarize_with_progress(ctx, &summarizer, &bytes, &request).await {
+        Ok(output) => {
+            ctx.send(super::summary_reply(&label, &output)).await?;
+            // Only a summary of the whole, finished session replaces the stored one.
+            if scope.is_empty() {
+                super::store_summary(Arc::clone(&state.caption_sink), file_name, &output).await;
+            }
+        }
+        Err(err) => {
+            tracing::error!(?err, session = %session_id, "On-demand summary failed");
+            ctx.say("The summary request failed; try again later")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Portion of a session to summarize, as offsets from its start or a trailing window.
+struct TimeRange {
+    from: Option<ChronoDuration>,
+    to: Option<ChronoDuration>,
+    last: Option<ChronoDuration>,
+}
+
+impl TimeRange {
+    /// Errors are user-facing.
+    fn from_options(
+        last_minutes: Option<u32>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Self, String> {
+        let from = from
+            .map(|raw| {
+                parse_offset(raw).ok_or_else(|| {
+                    format!("`from` must look like 45 or 1:15 (at most 48:00), not `{raw}`")
+                })
+            })
+            .transpose()?;
+        let to = to
+            .map(|raw| {
+                parse_offset(raw).ok_or_else(|| {
+                    format!("`to` must look like 90 or 1:30 (at most 48:00), not `{raw}`")
+                })
+            })
+            .transpose()?;
+        if last_minutes.is_some() && (from.is_some() || to.is_some()) {
+            return Err("Use either `last_minutes` or `from`/`to`, not both".to_string());
+        }
+        if let (Some(from), Some(to)) = (from, to)
+            && from >= to
+        {
+            return Err("`from` must be earlier than `to`".to_string());
+        }
+        Ok(Self {
+            from,
+            to,
+            last: last_minutes.map(|minutes| ChronoDuration::minutes(i64::from(minutes))),
+        })
+    }
+
+    fn is_unbounded(&self) -> bool {
+        self.from.is_none() && self.to.is_none() && self.last.is_none()
+    }
+
+    fn describe(&self) -> Option<String> {
+        if let Some(last) = self.last {
+            return Some(format!("last {} min", last.num_minutes()));
+        }
+        match (self.from, self.to) {
+            (None, None) => None,
+            (from, to) => Some(format!(
+                "{}–{}",
+                from.map(format_offset)
+                    .unwrap_or_else(|| "start".to_string()),
+                to.map(format_offset).unwrap_or_else(|| "end".to_string())
+            )),
+        }
+    }
+}
+
+/// Speakers to keep, matched by Discord id or case-insensitive display name.
+struct SpeakerFilter {
+    ids: Vec<UserId>,
+    names: Vec<String>,
+}
+
+impl SpeakerFilter {
+    /// Accepts `<@id>` mentions, raw ids, and names separated by commas.
+    fn parse(raw: &str) -> Self {
+        let mut filter = Self {
+            ids: Vec::new(),
+            names: Vec::new(),
+        };
+        for token in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+        {
+            let digits = token
+                .strip_prefix("<@")
+                .and_then(|rest| rest.strip_suffix('>'))
+                .map(|rest| rest.trim_start_matches('!'))
+                .unwrap_or(token);
+            match digits.parse::<u64>().ok().filter(|id| *id > 0) {
+                Some(id) => filter.ids.push(UserId::new(id)),
+                None => filter.names.push(token.to_string()),
+            }
+        }
+        filter
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.names.is_empty()
+    }
+
+    fn matches(&self, entry: &CaptionEntry) -> bool {
+        entry.speaker.id.is_some_and(|id| self.ids.contains(&id))
+            || self
+                .names
+                .iter()
+                .any(|name| entry.speaker.name.eq_ignore_ascii_case(name))
+    }
+
+    fn describe(&self) -> String {
+        let count = self.ids.len() + self.names.len();
+        if count == 1 {
+            "1 speaker".to_string()
+        } else {
+            format!("{count} speakers")
+        }
+    }
+}
+
+/// Drops entries outside `range` or not spoken by `speakers`. Trailing windows end at
+/// `now` for sessions still recording and at the last caption otherwise.
+fn filter_entries(
+    document: &mut SessionDocument,
+    range: &TimeRange,
+    speakers: Option<&SpeakerFilter>,
+    now: Option<DateTime<FixedOffset>>,
+) {
+    if let Some(speakers) = speakers {
+        document
+            .transcriptions
+            .retain(|entry| speakers.matches(entry));
+    }
+    if range.is_unbounded() {
+        return;
+    }
+
+    let started = DateTime::parse_from_rfc3339(&document.metadata.started_at).ok();
+    let timestamps: Vec<Option<DateTime<FixedOffset>>> = document
+        .transcriptions
+        .iter()
+        .map(|entry| DateTime::parse_from_rfc3339(&entry.timestamp).ok())
+        .collect();
+    let window_start = match range.last {
+        Some(last) => now
+            .or_else(|| timestamps.iter().flatten().max().copied())
+            .map(|end| end - last),
+        None => None,
+    };
+    let mut timestamps = timestamps.into_iter();
+    document.transcriptions.retain(|_| {
+        let Some(at) = timestamps.next().flatten() else {
+            return false;
+        };
+        if let Some(window_start) = window_start {
+            return at >= window_start;
+        }
+        let Some(started) = started else {
+            return false;
+        };
+        let offset = at - started;
+        range.from.is_none_or(|from| offset >= from) && range.to.is_none_or(|to| offset <= to)
+    });
+}
+
+/// `45` is minutes; `1:15` is hours and minutes; `1:15:30` adds seconds. Offsets past
+/// [`MAX_OFFSET_HOURS`] are rejected rather than overflowing.
+fn parse_offset(raw: &str) -> Option<ChronoDuration> {
+    let parts: Vec<i64> = raw
+        .trim()
+        .split(':')
+        .map(|part| part.trim().parse::<i64>().ok().filter(|value| *value >= 0))
+        .collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [minutes] => (0, *minutes, 0),
+        [hours, minutes] if *minutes < 60 => (*hours, *minutes, 0),
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => (*hours, *minutes, *seconds),
+        _ => return None,
+    };
+    let offset = ChronoDuration::try_hours(hours)?
+        .checked_add(&ChronoDuration::try_minutes(minutes)?)?
+        .checked_add(&ChronoDuration::try_seconds(seconds)?)?;
+    (offset <= ChronoDuration::hours(MAX_OFFSET_HOURS)).then_some(offset)
+}
+
+fn format_offset(offset: ChronoDuration) -> String {
+    let minutes = offset.num_minutes();
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
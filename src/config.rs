@@ -1,15 +1,46 @@
-use std::{env, net::SocketAddr, path::PathBuf, time::Duration};
+use std::{
+    env,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::{Context, anyhow, bail};
 use which::which;
 
+use crate::{
+    captions::{
+        database::TranscriptDbMode,
+        storage::{S3Config, StorageConfig},
+    },
+    digest::DigestConfig,
+    redaction::{Detector, RedactionConfig, RedactionMode},
+    retention::{self, RetentionAction, RetentionLimits, RetentionPolicy},
+    summaries::{
+        SummaryConfig, SummaryFormat, SummaryProvider, SummaryQueueConfig, SummaryTemplates,
+    },
+    webhooks::{WebhookConfig, WebhookEvent},
+};
+
 const DEFAULT_ENTRY_SOUND_VOLUME: f32 = 0.5;
+const DEFAULT_S3_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// Shortest spacing allowed between running notes, to keep provider costs sane.
+pub const MIN_ROLLING_SUMMARY_MINUTES: u64 = 5;
+
+#[derive(Clone, Debug)]
+pub struct TranscriptDbConfig {
+    pub path: PathBuf,
+    pub mode: TranscriptDbMode,
+}
 
 #[derive(Clone, Debug)]
 pub struct BotConfig {
     pub discord_token: String,
     pub whisper_model_path: PathBuf,
     pub caption_dir: PathBuf,
+    pub storage: StorageConfig,
+    pub transcript_db: Option<TranscriptDbConfig>,
     pub chunk_duration: Duration,
     pub sample_rate: u32,
     pub whisper_language: Option<String>,
@@ -19,10 +50,16 @@ pub struct BotConfig {
     pub whisper_gpu_device: i32,
     pub entry_sound_path: PathBuf,
     pub entry_sound_volume: f32,
-    pub openai_api_key: Option<String>,
-    pub openai_model: String,
+    pub summary: Option<SummaryConfig>,
     pub include_transcripts_with_summary: bool,
     pub http_bind_addr: SocketAddr,
+    pub http_api_token: Option<String>,
+    pub live_caption_flush_interval: Duration,
+    pub transcription_stall_threshold: Duration,
+    pub redaction: RedactionConfig,
+    pub retention: RetentionPolicy,
+    pub webhooks: WebhookConfig,
+    pub digest: DigestConfig,
 }
 
 impl BotConfig {
@@ -80,8 +117,7 @@ impl BotConfig {
             .and_then(|raw| raw.parse::<f32>().ok())
             .map(|value| value.clamp(0.0, 1.0))
             .unwrap_or(DEFAULT_ENTRY_SOUND_VOLUME);
-        let openai_api_key = env::var("OPENAPI_KEY").ok();
-        let openai_model = env::var("OPENAPI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+        let summary = Self::summary_from_env(&caption_dir)?;
         let include_transcripts_with_summary = env::var("INCLUDE_TRANSCRIPTS_WITH_SUMMARY")
             .ok()
             .and_then(|raw| Self::parse_bool(&raw))
@@ -90,10 +126,36 @@ impl BotConfig {
             .unwrap_or_else(|_| "0.0.0.0:8080".to_string())
             .parse()
             .context("Invalid HTTP_BIND_ADDR value")?;
+        let http_api_token = env::var("HTTP_API_TOKEN")
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|token| !token.is_empty());
 
-        if openai_api_key.is_none() && !include_transcripts_with_summary {
+        let live_caption_flush_secs = env::var("LIVE_CAPTION_FLUSH_SECS")
+            .ok()
+            .and_then(|raw| raw.parse::<f32>().ok())
+            .map(|secs| secs.max(1.0))
+            .unwrap_or(3.0);
+        let transcription_stall_secs = env::var("TRANSCRIPTION_STALL_SECS")
+            .ok()
+            .and_then(|raw| raw.parse::<u64>().ok())
+            .map(|secs| secs.max(10))
+            .unwrap_or(120);
+        let redaction = Self::redaction_from_env()?;
+        let retention = Self::retention_from_env()?;
+        let storage = Self::storage_from_env(&caption_dir)?;
+        let transcript_db = Self::transcript_db_from_env()?;
+        let webhooks = Self::webhooks_from_env(&caption_dir)?;
+        let digest = Self::digest_from_env(&caption_dir)?;
+
+        if summary.is_none() && !include_transcripts_with_summary {
             bail!(
-                "INCLUDE_TRANSCRIPTS_WITH_SUMMARY=false requires OPENAPI_KEY; summary-only flow is not possible without an OpenAI key"
+                "INCLUDE_TRANSCRIPTS_WITH_SUMMARY=false requires a summary provider; configure SUMMARY_API_KEY (or SUMMARY_BASE_URL for a local chat server)"
+            );
+        }
+        if summary.is_none() && digest.is_enabled() {
+            bail!(
+                "DIGEST_CHANNELS requires a summary provider; configure SUMMARY_API_KEY (or SUMMARY_BASE_URL for a local chat server)"
             );
         }
 
@@ -101,6 +163,8 @@ impl BotConfig {
             discord_token,
             whisper_model_path,
             caption_dir,
+            storage,
+            transcript_db,
             chunk_duration: Duration::from_secs_f32(chunk_secs),
             sample_rate,
             whisper_language,
@@ -110,10 +174,16 @@ impl BotConfig {
             whisper_gpu_device,
             entry_sound_path,
             entry_sound_volume,
-            openai_api_key,
-            openai_model,
+            summary,
             include_transcripts_with_summary,
             http_bind_addr,
+            http_api_token,
+            live_caption_flush_interval: Duration::from_secs_f32(live_caption_flush_secs),
+            transcription_stall_threshold: Duration::from_secs(transcription_stall_secs),
+            redaction,
+            retention,
+            webhooks,
+            digest,
         })
     }
 
@@ -147,6 +217,367 @@ impl BotConfig {
         &self.whisper_model_name
     }
 
+    fn redaction_from_env() -> anyhow::Result<RedactionConfig> {
+        let mode = match env::var("REDACTION_MODE") {
+            Ok(raw) => RedactionMode::parse(&raw).ok_or_else(|| {
+                anyhow!("Invalid REDACTION_MODE '{raw}' (expected off, mask, export, or tag)")
+            })?,
+            Err(_) => RedactionMode::Off,
+        };
+        let detectors = match env::var("REDACTION_DETECTORS") {
+            Ok(raw) => {
+                let mut requested = Vec::new();
+                for name in raw.split(',').filter(|name| !name.trim().is_empty()) {
+                    let detector = Detector::parse(name)
+                        .ok_or_else(|| anyhow!("Unknown redaction detector '{}'", name.trim()))?;
+                    requested.push(detector);
+                }
+                Detector::ALL
+                    .into_iter()
+                    .filter(|detector| requested.contains(detector))
+                    .collect()
+            }
+            Err(_) => Detector::ALL.to_vec(),
+        };
+        let custom_patterns = match env::var("REDACTION_PATTERNS_FILE") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                let path = Self::absolute_path(PathBuf::from(raw))?;
+                RedactionConfig::load_patterns_file(&path)?
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(RedactionConfig {
+            mode,
+            detectors,
+            custom_patterns,
+        })
+    }
+
+    fn retention_from_env() -> anyhow::Result<RetentionPolicy> {
+        let action = match env::var("RETENTION_ACTION") {
+            Ok(raw) => RetentionAction::parse(&raw).ok_or_else(|| {
+                anyhow!("Invalid RETENTION_ACTION '{raw}' (expected delete or archive)")
+            })?,
+            Err(_) => RetentionAction::Archive,
+        };
+        let max_age = env::var("RETENTION_MAX_AGE_DAYS")
+            .ok()
+            .filter(|raw| !raw.trim().is_empty())
+            .map(|raw| retention::parse_days(&raw))
+            .transpose()
+            .context("Invalid RETENTION_MAX_AGE_DAYS value")?;
+        let max_bytes = env::var("RETENTION_MAX_BYTES_PER_GUILD")
+            .ok()
+            .filter(|raw| !raw.trim().is_empty())
+            .map(|raw| retention::parse_size(&raw))
+            .transpose()
+            .context("Invalid RETENTION_MAX_BYTES_PER_GUILD value")?;
+        let archive_max_age = env::var("RETENTION_ARCHIVE_MAX_AGE_DAYS")
+            .ok()
+            .filter(|raw| !raw.trim().is_empty())
+            .map(|raw| retention::parse_days(&raw))
+            .transpose()
+            .context("Invalid RETENTION_ARCHIVE_MAX_AGE_DAYS value")?;
+        let guild_overrides = match env::var("RETENTION_GUILD_OVERRIDES") {
+            Ok(raw) => RetentionPolicy::parse_overrides(&raw)
+                .context("Invalid RETENTION_GUILD_OVERRIDES value")?,
+            Err(_) => Default::default(),
+        };
+        let interval_secs = env::var("RETENTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|raw| raw.parse::<u64>().ok())
+            .map(|secs| secs.max(60))
+            .unwrap_or(3600);
+
+        Ok(RetentionPolicy {
+            action,
+            interval: Duration::from_secs(interval_secs),
+            defaults: RetentionLimits {
+                max_age,
+                max_bytes,
+                archive_max_age,
+            },
+            guild_overrides,
+        })
+    }
+
+    /// Also used by `hammock rotate-caption-keys`, which runs without Discord credentials.
+    pub fn storage_from_env(caption_dir: &Path) -> anyhow::Result<StorageConfig> {
+        let backend = env::var("CAPTION_STORAGE").unwrap_or_else(|_| "local".to_string());
+        match backend.trim().to_ascii_lowercase().as_str() {
+            "" | "local" | "fs" => Ok(StorageConfig::Local {
+                root: caption_dir.to_path_buf(),
+            }),
+            "s3" => {
+                let bucket = env::var("S3_BUCKET")
+                    .ok()
+                    .filter(|bucket| !bucket.trim().is_empty())
+                    .context("CAPTION_STORAGE=s3 requires S3_BUCKET")?;
+                let access_key_id = env::var("S3_ACCESS_KEY_ID")
+                    .or_else(|_| env::var("AWS_ACCESS_KEY_ID"))
+                    .context("CAPTION_STORAGE=s3 requires S3_ACCESS_KEY_ID or AWS_ACCESS_KEY_ID")?;
+                let secret_access_key = env::var("S3_SECRET_ACCESS_KEY")
+                    .or_else(|_| env::var("AWS_SECRET_ACCESS_KEY"))
+                    .context(
+                        "CAPTION_STORAGE=s3 requires S3_SECRET_ACCESS_KEY or AWS_SECRET_ACCESS_KEY",
+                    )?;
+                let session_token = env::var("AWS_SESSION_TOKEN")
+                    .ok()
+                    .filter(|token| !token.is_empty());
+                let region = env::var("S3_REGION")
+                    .or_else(|_| env::var("AWS_REGION"))
+                    .unwrap_or_else(|_| "us-east-1".to_string());
+                let endpoint = env::var("S3_ENDPOINT")
+                    .ok()
+                    .map(|raw| raw.trim().trim_end_matches('/').to_string())
+                    .filter(|raw| !raw.is_empty());
+                let path_style = env::var("S3_PATH_STYLE")
+                    .ok()
+                    .and_then(|raw| Self::parse_bool(&raw))
+                    .unwrap_or(endpoint.is_some());
+                let flush_interval = env::var("S3_FLUSH_SECONDS")
+                    .ok()
+                    .and_then(|raw| raw.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs)
+                    .unwrap_or(DEFAULT_S3_FLUSH_INTERVAL);
+                let prefix = env::var("S3_PREFIX")
+                    .map(|raw| raw.trim().trim_matches('/').to_string())
+                    .unwrap_or_default();
+                let prefix = if prefix.is_empty() {
+                    prefix
+                } else {
+                    format!("{prefix}/")
+                };
+                Ok(StorageConfig::S3(S3Config {
+                    bucket: bucket.trim().to_string(),
+                    prefix,
+                    region,
+                    endpoint,
+                    path_style,
+                    access_key_id,
+                    secret_access_key,
+                    session_token,
+                    flush_interval,
+                }))
+            }
+            other => bail!("Invalid CAPTION_STORAGE '{other}' (expected local or s3)"),
+        }
+    }
+
+    /// Returns `None` when summaries are disabled: hosted providers need an API key,
+    /// while the chat provider is also enabled by a `SUMMARY_BASE_URL` alone so keyless
+    /// local servers (Ollama, llama.cpp) work.
+    fn summary_from_env(caption_dir: &Path) -> anyhow::Result<Option<SummaryConfig>> {
+        let non_empty = |name: &str| {
+            env::var(name)
+                .ok()
+                .map(|raw| raw.trim().to_string())
+                .filter(|raw| !raw.is_empty())
+        };
+        let provider = match non_empty("SUMMARY_PROVIDER") {
+            Some(raw) => SummaryProvider::parse(&raw).ok_or_else(|| {
+                anyhow!("Invalid SUMMARY_PROVIDER '{raw}' (expected responses, chat, or anthropic)")
+            })?,
+            None => SummaryProvider::OpenAiResponses,
+        };
+        let explicit_base_url = non_empty("SUMMARY_BASE_URL");
+        let api_key = non_empty("SUMMARY_API_KEY").or_else(|| match provider {
+            SummaryProvider::Anthropic => non_empty("ANTHROPIC_API_KEY"),
+            _ => non_empty("OPENAPI_KEY"),
+        });
+
+        let enabled = match provider {
+            SummaryProvider::ChatCompletions => api_key.is_some() || explicit_base_url.is_some(),
+            _ => api_key.is_some(),
+        };
+        if !enabled {
+            return Ok(None);
+        }
+
+        let model = match non_empty("SUMMARY_MODEL").or_else(|| non_empty("OPENAPI_MODEL")) {
+            Some(model) => model,
+            None if provider == SummaryProvider::Anthropic => {
+                bail!("SUMMARY_PROVIDER=anthropic requires SUMMARY_MODEL")
+            }
+            None => "gpt-4o-mini".to_string(),
+        };
+        let base_url = explicit_base_url
+            .unwrap_or_else(|| provider.default_base_url().to_string())
+            .trim_end_matches('/')
+            .to_string();
+        let max_tokens = match non_empty("SUMMARY_MAX_TOKENS") {
+            Some(raw) => raw
+                .parse::<u32>()
+                .ok()
+                .filter(|tokens| *tokens > 0)
+                .ok_or_else(|| anyhow!("Invalid SUMMARY_MAX_TOKENS '{raw}'"))?,
+            None => 1024,
+        };
+        let chunk_tokens = match non_empty("SUMMARY_CHUNK_TOKENS") {
+            Some(raw) => raw
+                .parse::<usize>()
+                .ok()
+                .filter(|tokens| *tokens >= 500)
+                .ok_or_else(|| anyhow!("Invalid SUMMARY_CHUNK_TOKENS '{raw}' (minimum 500)"))?,
+            None => 12_000,
+        };
+        let format = match non_empty("SUMMARY_FORMAT") {
+            Some(raw) => SummaryFormat::parse(&raw).ok_or_else(|| {
+                anyhow!("Invalid SUMMARY_FORMAT '{raw}' (expected markdown or structured)")
+            })?,
+            None => SummaryFormat::Markdown,
+        };
+        let templates = match non_empty("SUMMARY_TEMPLATES_PATH") {
+            Some(raw) => SummaryTemplates::load(&Self::absolute_path(PathBuf::from(raw))?)?,
+            None => SummaryTemplates::default(),
+        };
+        let queue_path = match non_empty("SUMMARY_QUEUE_PATH") {
+            Some(raw) => Self::absolute_path(PathBuf::from(raw))?,
+            None => caption_dir.join(".summary-jobs.json"),
+        };
+        let rolling_interval = match non_empty("SUMMARY_ROLLING_MINUTES") {
+            Some(raw) => match raw.parse::<u64>() {
+                Ok(0) => None,
+                Ok(minutes) => Some(Duration::from_secs(
+                    minutes.max(MIN_ROLLING_SUMMARY_MINUTES) * 60,
+                )),
+                Err(_) => bail!("Invalid SUMMARY_ROLLING_MINUTES '{raw}'"),
+            },
+            None => None,
+        };
+        let max_attempts = non_empty("SUMMARY_MAX_ATTEMPTS")
+            .and_then(|raw| raw.parse::<u32>().ok())
+            .map(|attempts| attempts.clamp(1, 20))
+            .unwrap_or(8);
+
+        Ok(Some(SummaryConfig {
+            provider,
+            base_url,
+            api_key,
+            model,
+            max_tokens,
+            chunk_tokens,
+            format,
+            templates: Arc::new(templates),
+            queue: SummaryQueueConfig {
+                path: queue_path,
+                max_attempts,
+            },
+            rolling_interval,
+        }))
+    }
+
+    /// Also used by `hammock import-sessions`, which runs without Discord credentials.
+    pub fn transcript_db_from_env() -> anyhow::Result<Option<TranscriptDbConfig>> {
+        let path = match env::var("TRANSCRIPT_DB_PATH") {
+            Ok(raw) if !raw.trim().is_empty() => Self::absolute_path(PathBuf::from(raw.trim()))?,
+            _ => return Ok(None),
+        };
+        let mode = match env::var("TRANSCRIPT_DB_MODE") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                TranscriptDbMode::parse(&raw).ok_or_else(|| {
+                    anyhow!("Invalid TRANSCRIPT_DB_MODE '{raw}' (expected mirror or primary)")
+                })?
+            }
+            _ => TranscriptDbMode::Mirror,
+        };
+        Ok(Some(TranscriptDbConfig { path, mode }))
+    }
+
+    /// Digest schedules from `DIGEST_CHANNELS`, posted at `DIGEST_HOUR` (weekly ones on
+    /// `DIGEST_WEEKDAY`), with delivery state kept beside the captions.
+    fn digest_from_env(caption_dir: &Path) -> anyhow::Result<DigestConfig> {
+        let schedules = match env::var("DIGEST_CHANNELS") {
+            Ok(raw) => {
+                DigestConfig::parse_schedules(&raw).context("Invalid DIGEST_CHANNELS value")?
+            }
+            Err(_) => Vec::new(),
+        };
+        let hour = match env::var("DIGEST_HOUR") {
+            Ok(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|hour| *hour < 24)
+                .ok_or_else(|| anyhow!("Invalid DIGEST_HOUR '{raw}' (expected 0-23)"))?,
+            _ => 9,
+        };
+        let weekday = match env::var("DIGEST_WEEKDAY") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                raw.trim().parse::<chrono::Weekday>().map_err(|_| {
+                    anyhow!("Invalid DIGEST_WEEKDAY '{raw}' (expected mon through sun)")
+                })?
+            }
+            _ => chrono::Weekday::Mon,
+        };
+        Ok(DigestConfig {
+            schedules,
+            hour,
+            weekday,
+            state_path: caption_dir.join(".digest-state.json"),
+        })
+    }
+
+    /// Also used by `hammock send-test-webhook`, which runs without Discord credentials.
+    pub fn webhooks_from_env(caption_dir: &Path) -> anyhow::Result<WebhookConfig> {
+        let endpoints: Vec<String> = env::var("WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect();
+        for url in &endpoints {
+            reqwest::Url::parse(url).with_context(|| format!("Invalid webhook URL '{url}'"))?;
+        }
+        let secret = env::var("WEBHOOK_SECRET")
+            .ok()
+            .filter(|secret| !secret.trim().is_empty());
+        let events = match env::var("WEBHOOK_EVENTS") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                let mut requested = Vec::new();
+                for name in raw.split(',').filter(|name| !name.trim().is_empty()) {
+                    let event = WebhookEvent::parse(name)
+                        .ok_or_else(|| anyhow!("Unknown webhook event '{}'", name.trim()))?;
+                    requested.push(event);
+                }
+                WebhookEvent::ALL
+                    .into_iter()
+                    .filter(|event| requested.contains(event))
+                    .collect()
+            }
+            _ => WebhookEvent::ALL.to_vec(),
+        };
+        let max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|raw| raw.parse::<u32>().ok())
+            .map(|attempts| attempts.clamp(1, 20))
+            .unwrap_or(5);
+        let dead_letter_path = match env::var("WEBHOOK_DEAD_LETTER_PATH") {
+            Ok(raw) if !raw.trim().is_empty() => Self::absolute_path(PathBuf::from(raw))?,
+            _ => caption_dir.join("webhook-dead-letters.jsonl"),
+        };
+        let include_document = env::var("WEBHOOK_INCLUDE_DOCUMENT")
+            .ok()
+            .and_then(|raw| Self::parse_bool(&raw))
+            .unwrap_or(true);
+        let public_base_url = env::var("PUBLIC_BASE_URL")
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|url| !url.is_empty());
+
+        Ok(WebhookConfig {
+            endpoints,
+            secret,
+            events,
+            max_attempts,
+            dead_letter_path,
+            include_document,
+            public_base_url,
+        })
+    }
+
     fn parse_bool(raw: &str) -> Option<bool> {
         match raw.trim().to_ascii_lowercase().as_str() {
             "1" | "true" | "yes" | "on" => Some(true),
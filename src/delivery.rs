@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use serenity::{
+    builder::{CreateAttachment, CreateMessage, EditMessage},
+    http::Http,
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId},
+    },
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    BotState,
+    captions::SessionSummary,
+    commands,
+    summaries::{SummaryJob, SummaryProgress},
+};
+
+/// Posts a finished session's transcript and summary to a text channel.
+///
+/// Everything goes out as plain channel messages rather than interaction replies, so
+/// results reach the channel whether the session ended through `/leave`, the admin API,
+/// the bot being disconnected, or shutdown.
+pub struct SessionDelivery<'a> {
+    state: &'a BotState,
+    http: &'a Http,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+}
+
+impl<'a> SessionDelivery<'a> {
+    pub fn new(
+        state: &'a BotState,
+        http: &'a Http,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> Self {
+        Self {
+            state,
+            http,
+            guild_id,
+            channel_id,
+        }
+    }
+
+    /// Uploads the transcript, then makes the first summary attempt and posts the
+    /// summary, or where it will appear once a retry succeeds.
+    pub async fn deliver(&self, summary: &SessionSummary) {
+        self.upload_transcript(summary).await;
+        if let Some(job) = self.enqueue_summary(summary) {
+            self.run_summary(&job).await;
+        }
+    }
+
+    /// Uploads the export-redacted session document, unless summaries are enabled and
+    /// `INCLUDE_TRANSCRIPTS_WITH_SUMMARY` is off.
+    pub async fn upload_transcript(&self, summary: &SessionSummary) {
+        let label = transcript_label(summary);
+        if self.state.summarizer().is_some() && !self.state.include_transcripts_with_summary() {
+            tracing::info!(
+                %label,
+                "Skipping transcript upload because INCLUDE_TRANSCRIPTS_WITH_SUMMARY is disabled"
+            );
+            return;
+        }
+
+        let sink = Arc::clone(&self.state.caption_sink);
+        let file_name = summary.file_name.clone();
+        let mut document =
+            match tokio::task::spawn_blocking(move || sink.read_session_document(&file_name)).await
+            {
+                Ok(Ok(document)) => document,
+                Ok(Err(err)) => {
+                    tracing::error!(?err, "Failed reading caption file for upload");
+                    return;
+                }
+                Err(err) => {
+                    tracing::error!(?err, "Caption upload task failed");
+                    return;
+                }
+            };
+        self.state
+            .redactor
+            .redact_document_for_export(&mut document);
+        let minified = match serde_json::to_string(&document) {
+            Ok(minified) => minified,
+            Err(err) => {
+                tracing::warn!(?err, "Failed to serialize caption JSON before upload");
+                return;
+            }
+        };
+        let message = CreateMessage::new()
+            .content(format!("{label} ({})", summary.duration_hms()))
+            .add_file(CreateAttachment::bytes(
+                minified.into_bytes(),
+                format!("{label}.json"),
+            ));
+        if let Err(err) = self.channel_id.send_message(self.http, message).await {
+            tracing::warn!(?err, channel = %self.channel_id, "Failed to upload transcript");
+        }
+    }
+
+    /// Records the summary owed for the session and returns it claimed. `None` when
+    /// summaries are disabled or an attempt for the session is already running.
+    pub fn enqueue_summary(&self, summary: &SessionSummary) -> Option<SummaryJob> {
+        let queue = self.state.summary_queue()?;
+        queue.enqueue(SummaryJob::new(
+            summary.session_id(),
+            summary.file_name.clone(),
+            self.guild_id,
+            self.channel_id,
+            transcript_label(summary),
+            summary.title.clone(),
+        ))
+    }
+
+    /// Makes one attempt at a claimed job, relaying progress for long sessions.
+    pub async fn run_summary(&self, job: &SummaryJob) {
+        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+        let (result, ()) = tokio::join!(
+            self.state.run_summary_job(job, Some(progress_tx)),
+            self.relay_progress(progress_rx)
+        );
+        let message = match result {
+            Ok(output) => commands::summary_message(&job.label, &output),
+            Err(_) => {
+                let Some(queue) = self.state.summary_queue() else {
+                    return;
+                };
+                CreateMessage::new().content(commands::summary_failure_message(
+                    &queue,
+                    &job.session_id,
+                    job.channel_id,
+                ))
+            }
+        };
+        if let Err(err) = self.channel_id.send_message(self.http, message).await {
+            tracing::warn!(?err, session = %job.session_id, "Failed to post session summary");
+        }
+    }
+
+    async fn relay_progress(&self, mut updates: mpsc::UnboundedReceiver<SummaryProgress>) {
+        let mut message: Option<Message> = None;
+        while let Some(update) = updates.recv().await {
+            let content = commands::progress_text(update);
+            let result = match message.as_mut() {
+                Some(message) => message
+                    .edit(self.http, EditMessage::new().content(content))
+                    .await
+                    .map(|()| None),
+                None => self.channel_id.say(self.http, content).await.map(Some),
+            };
+            match result {
+                Ok(Some(posted)) => message = Some(posted),
+                Ok(None) => {}
+                Err(err) => tracing::warn!(?err, "Failed to post summary progress"),
+            }
+        }
+    }
+}
+
+pub fn transcript_label(summary: &SessionSummary) -> String {
+    match summary.title.as_deref() {
+        Some(title) => format!("{} - {} - Transcription log", summary.date_label(), title),
+        None => format!("{} - Transcription log", summary.date_label()),
+    }
+}
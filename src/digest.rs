@@ -0,0 +1,445 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{
+    DateTime, Datelike, Days, Duration as ChronoDuration, Local, NaiveDate, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+use serenity::{
+    builder::{CreateAllowedMentions, CreateEmbed, CreateMessage},
+    http::Http,
+    model::id::{ChannelId, GuildId, UserId},
+};
+
+use crate::{
+    BotState, Data,
+    captions::index::IndexedSession,
+    summaries::{DigestSession, TranscriptSummarizer},
+    utils::truncate,
+};
+
+const DIGEST_POLL: Duration = Duration::from_secs(60);
+/// Wait before retrying a digest whose summary or post failed.
+const RETRY_MINUTES: i64 = 15;
+/// Speaking rate used to estimate talk time from transcribed words.
+const WORDS_PER_MINUTE: usize = 150;
+/// Caption lines sent for a session that has no stored summary.
+const MAX_EXCERPT_LINES: usize = 200;
+const MAX_LISTED_PARTICIPANTS: usize = 15;
+const EMBED_COLOR: u32 = 0x5865F2;
+const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+const EMBED_FIELD_LIMIT: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestPeriod {
+    Daily,
+    Weekly,
+}
+
+impl DigestPeriod {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "daily" | "day" => Some(Self::Daily),
+            "weekly" | "week" => Some(Self::Weekly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    fn days(self) -> u64 {
+        match self {
+            Self::Daily => 1,
+            Self::Weekly => 7,
+        }
+    }
+}
+
+/// A digest of one guild's sessions, posted to `channel_id` every period.
+#[derive(Clone, Debug)]
+pub struct DigestSchedule {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub period: DigestPeriod,
+}
+
+impl DigestSchedule {
+    fn key(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.guild_id.get(),
+            self.channel_id.get(),
+            self.period.as_str()
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DigestConfig {
+    pub schedules: Vec<DigestSchedule>,
+    /// Local hour at which periods end and digests are posted.
+    pub hour: u32,
+    /// Day weekly digests are posted on.
+    pub weekday: Weekday,
+    /// Records the last period posted per schedule, so restarts neither skip nor repeat
+    /// a digest.
+    pub state_path: PathBuf,
+}
+
+impl DigestConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.schedules.is_empty()
+    }
+
+    /// Parses `guild_id:channel_id[:daily|weekly]` items separated by commas.
+    pub fn parse_schedules(raw: &str) -> Result<Vec<DigestSchedule>> {
+        let mut schedules = Vec::new();
+        for item in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let mut parts = item.split(':').map(str::trim);
+            let mut id = |what: &str| {
+                parts
+                    .next()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .filter(|value| *value > 0)
+                    .ok_or_else(|| anyhow!("invalid {what} id in digest schedule '{item}'"))
+            };
+            let guild_id = GuildId::new(id("guild")?);
+            let channel_id = ChannelId::new(id("channel")?);
+            let period = match parts.next() {
+                Some("") | None => DigestPeriod::Daily,
+                Some(raw) => DigestPeriod::parse(raw).ok_or_else(|| {
+                    anyhow!("invalid period in digest schedule '{item}' (expected daily or weekly)")
+                })?,
+            };
+            schedules.push(DigestSchedule {
+                guild_id,
+                channel_id,
+                period,
+            });
+        }
+        Ok(schedules)
+    }
+
+    /// Most recent scheduled end of `period` at or before `now`.
+    fn latest_boundary(&self, period: DigestPeriod, now: DateTime<Local>) -> DateTime<Local> {
+        let mut date = now.date_naive();
+        // Two weeks of candidates covers a weekday whose hour falls in a DST gap.
+        for _ in 0..15 {
+            if (period == DigestPeriod::Daily || date.weekday() == self.weekday)
+                && let Some(at) = local_at(date, self.hour)
+                && at <= now
+            {
+                return at;
+            }
+            date = date - Days::new(1);
+        }
+        now
+    }
+
+    fn period_start(&self, period: DigestPeriod, end: DateTime<Local>) -> DateTime<Local> {
+        let days = Days::new(period.days());
+        local_at(end.date_naive() - days, self.hour)
+            .unwrap_or_else(|| end - ChronoDuration::days(period.days() as i64))
+    }
+}
+
+fn local_at(date: NaiveDate, hour: u32) -> Option<DateTime<Local>> {
+    date.and_hms_opt(hour, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct DigestState {
+    /// End of the last period posted, keyed by `guild:channel:period`.
+    #[serde(default)]
+    posted: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl DigestState {
+    fn load(path: &Path) -> Result<Self> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("parsing digest state {}", path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => {
+                Err(err).with_context(|| format!("reading digest state {}", path.display()))
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let bytes = serde_json::to_vec_pretty(self)?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes).with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))?;
+        Ok(())
+    }
+}
+
+pub fn spawn_digest_task(
+    config: DigestConfig,
+    state: Data,
+    summarizer: TranscriptSummarizer,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run_digests(config, state, summarizer))
+}
+
+/// Posts each schedule's digest once its period ends. A schedule seen for the first
+/// time starts with the next period; after downtime only the latest missed period is
+/// posted.
+async fn run_digests(config: DigestConfig, state: Data, summarizer: TranscriptSummarizer) {
+    let mut digest_state = DigestState::load(&config.state_path).unwrap_or_else(|err| {
+        tracing::error!(?err, "Failed to load digest state; starting fresh");
+        DigestState::default()
+    });
+    let mut retry_at: HashMap<String, DateTime<Utc>> = HashMap::new();
+    let mut ticker = tokio::time::interval(DIGEST_POLL);
+    loop {
+        ticker.tick().await;
+        let Some(ctx) = state.discord_context() else {
+            continue;
+        };
+        let now = Local::now();
+        let mut changed = false;
+        for schedule in &config.schedules {
+            let key = schedule.key();
+            let end = config.latest_boundary(schedule.period, now);
+            let end_utc = end.with_timezone(&Utc);
+            let Some(last) = digest_state.posted.get(&key).copied() else {
+                digest_state.posted.insert(key, end_utc);
+                changed = true;
+                continue;
+            };
+            if last >= end_utc || retry_at.get(&key).is_some_and(|at| *at > Utc::now()) {
+                continue;
+            }
+
+            let start = config.period_start(schedule.period, end);
+            match post_digest(&ctx.http, &state, &summarizer, schedule, start, end).await {
+                Ok(()) => {
+                    retry_at.remove(&key);
+                    digest_state.posted.insert(key, end_utc);
+                    changed = true;
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        ?err,
+                        guild = %schedule.guild_id,
+                        period = schedule.period.as_str(),
+                        "Session digest failed; retrying in {RETRY_MINUTES} minutes"
+                    );
+                    retry_at.insert(key, Utc::now() + ChronoDuration::minutes(RETRY_MINUTES));
+                }
+            }
+        }
+        if changed && let Err(err) = digest_state.save(&config.state_path) {
+            tracing::error!(?err, "Failed to persist digest state");
+        }
+    }
+}
+
+async fn post_digest(
+    http: &Http,
+    state: &BotState,
+    summarizer: &TranscriptSummarizer,
+    schedule: &DigestSchedule,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<()> {
+    let index = state.session_index();
+    let guild_id = schedule.guild_id;
+    let (start_at, end_at) = (start.fixed_offset(), end.fixed_offset());
+    let mut sessions: Vec<IndexedSession> = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        Ok::<_, anyhow::Error>(index.sessions_for_guild(guild_id))
+    })
+    .await??
+    .into_iter()
+    .filter(|session| {
+        session
+            .ended_at
+            .is_some_and(|ended| ended >= start_at && ended < end_at)
+    })
+    .collect();
+    if sessions.is_empty() {
+        tracing::info!(
+            guild = %guild_id,
+            period = schedule.period.as_str(),
+            "No sessions finished in digest period; nothing to post"
+        );
+        return Ok(());
+    }
+    sessions.reverse();
+    let stats = DigestStats::from_sessions(&sessions);
+
+    let sink = Arc::clone(&state.caption_sink);
+    let inputs = tokio::task::spawn_blocking(move || {
+        sessions
+            .iter()
+            .map(|session| DigestSession {
+                label: session_label(session),
+                summary: sink.read_summary_text(&session.id).unwrap_or_else(|err| {
+                    tracing::debug!(?err, session = %session.id, "Stored summary unreadable");
+                    None
+                }),
+                lines: session
+                    .lines
+                    .iter()
+                    .take(MAX_EXCERPT_LINES)
+                    .map(|line| format!("{}: {}", line.speaker, line.comment))
+                    .collect(),
+            })
+            .collect::<Vec<_>>()
+    })
+    .await?;
+
+    let period = describe_period(schedule.period, start, end);
+    let digest = summarizer.digest(&period, &inputs).await?;
+    let title = match schedule.period {
+        DigestPeriod::Daily => format!("Daily digest — {period}"),
+        DigestPeriod::Weekly => format!("Weekly digest — {period}"),
+    };
+    schedule
+        .channel_id
+        .send_message(
+            http,
+            CreateMessage::new()
+                .embed(stats.to_embed(&title, &digest))
+                .allowed_mentions(CreateAllowedMentions::new()),
+        )
+        .await?;
+    tracing::info!(
+        guild = %guild_id,
+        period = schedule.period.as_str(),
+        sessions = stats.sessions,
+        "Posted session digest"
+    );
+    Ok(())
+}
+
+fn session_label(session: &IndexedSession) -> String {
+    let name = session.title.as_deref().unwrap_or(&session.id);
+    match session.started_at {
+        Some(started) => format!(
+            "{name} ({})",
+            started.with_timezone(&Local).format("%a %H:%M")
+        ),
+        None => name.to_string(),
+    }
+}
+
+fn describe_period(period: DigestPeriod, start: DateTime<Local>, end: DateTime<Local>) -> String {
+    match period {
+        DigestPeriod::Daily => end.format("%b %-d").to_string(),
+        DigestPeriod::Weekly => format!("{}–{}", start.format("%b %-d"), end.format("%b %-d")),
+    }
+}
+
+/// Totals shown beside the digest text.
+struct DigestStats {
+    sessions: usize,
+    call_time: ChronoDuration,
+    talk_time: ChronoDuration,
+    /// Speakers with their estimated talk time, most talkative first.
+    participants: Vec<(String, ChronoDuration)>,
+}
+
+impl DigestStats {
+    fn from_sessions(sessions: &[IndexedSession]) -> Self {
+        let call_time = sessions
+            .iter()
+            .filter_map(|session| Some(session.ended_at? - session.started_at?))
+            .filter(|elapsed| *elapsed > ChronoDuration::zero())
+            .sum();
+
+        let mut words: HashMap<String, usize> = HashMap::new();
+        for line in sessions.iter().flat_map(|session| &session.lines) {
+            let speaker = match line.speaker_id {
+                Some(id) => mention(id),
+                None => format!("**{}**", line.speaker),
+            };
+            *words.entry(speaker).or_default() += line.comment.split_whitespace().count();
+        }
+        let talk = |count: usize| ChronoDuration::seconds((count * 60 / WORDS_PER_MINUTE) as i64);
+        let talk_time = talk(words.values().sum());
+        let mut participants: Vec<(String, ChronoDuration)> = words
+            .into_iter()
+            .map(|(speaker, count)| (speaker, talk(count)))
+            .collect();
+        participants.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            sessions: sessions.len(),
+            call_time,
+            talk_time,
+            participants,
+        }
+    }
+
+    fn to_embed(&self, title: &str, digest: &str) -> CreateEmbed {
+        let mut listed: Vec<String> = self
+            .participants
+            .iter()
+            .take(MAX_LISTED_PARTICIPANTS)
+            .map(|(speaker, talk)| format!("{speaker} — ≈{}", format_duration(*talk)))
+            .collect();
+        if self.participants.len() > MAX_LISTED_PARTICIPANTS {
+            listed.push(format!(
+                "…and {} more",
+                self.participants.len() - MAX_LISTED_PARTICIPANTS
+            ));
+        }
+        let mut embed = CreateEmbed::new()
+            .title(truncate(title, 256))
+            .description(truncate(digest.trim(), EMBED_DESCRIPTION_LIMIT))
+            .color(EMBED_COLOR)
+            .field("Calls", self.sessions.to_string(), true)
+            .field("Call time", format_duration(self.call_time), true)
+            .field(
+                "Talk time",
+                format!("≈{}", format_duration(self.talk_time)),
+                true,
+            );
+        if !listed.is_empty() {
+            embed = embed.field(
+                format!("Participants ({})", self.participants.len()),
+                truncate(&listed.join("\n"), EMBED_FIELD_LIMIT),
+                false,
+            );
+        }
+        embed
+    }
+}
+
+fn mention(id: UserId) -> String {
+    format!("<@{}>", id.get())
+}
+
+fn format_duration(duration: ChronoDuration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
@@ -0,0 +1,209 @@
+use std::{sync::Arc, time::Duration};
+
+use serenity::{
+    builder::{CreateAllowedMentions, CreateMessage, EditMessage},
+    http::Http,
+    model::id::{ChannelId, GuildId, MessageId},
+};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+    time::{Instant, sleep_until},
+};
+
+use crate::{
+    captions::{CaptionEntry, CaptionEvent, CaptionSink},
+    redaction::Redactor,
+};
+
+/// Discord rejects messages over 2000 characters; leave room for the header.
+const MAX_MESSAGE_CHARS: usize = 1900;
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Posts captions for one voice session into a text channel, batching new entries
+/// and editing a rolling message until it fills up.
+pub struct LiveCaptionFeed {
+    target_channel: ChannelId,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+pub struct LiveFeedConfig {
+    pub http: Arc<Http>,
+    pub guild_id: GuildId,
+    pub voice_channel: ChannelId,
+    pub target_channel: ChannelId,
+    pub flush_interval: Duration,
+    pub redactor: Arc<Redactor>,
+}
+
+impl LiveCaptionFeed {
+    pub fn spawn(sink: &CaptionSink, config: LiveFeedConfig) -> Self {
+        let (stop, stop_rx) = watch::channel(false);
+        let target_channel = config.target_channel;
+        let task = tokio::spawn(run_feed(sink.subscribe(), stop_rx, config));
+        Self {
+            target_channel,
+            stop,
+            task,
+        }
+    }
+
+    pub fn target_channel(&self) -> ChannelId {
+        self.target_channel
+    }
+
+    /// Flushes any pending captions and waits for the feed task to finish.
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        if let Err(err) = self.task.await {
+            tracing::warn!("live caption task join error: {err}");
+        }
+    }
+}
+
+struct RollingMessage {
+    id: Option<MessageId>,
+    content: String,
+}
+
+async fn run_feed(
+    mut events: broadcast::Receiver<CaptionEvent>,
+    mut stop: watch::Receiver<bool>,
+    config: LiveFeedConfig,
+) {
+    let mut pending: Vec<String> = Vec::new();
+    let mut rolling = RollingMessage {
+        id: None,
+        content: String::new(),
+    };
+    let mut backoff = config.flush_interval;
+    let mut next_flush = Instant::now() + config.flush_interval;
+    let mut stopping = false;
+
+    loop {
+        tokio::select! {
+            event = events.recv(), if !stopping => match event {
+                Ok(event) => {
+                    if event.guild_id == config.guild_id && event.channel_id == config.voice_channel {
+                        pending.push(render_entry(&event.entry, &config.redactor));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    pending.push(format!("_… {skipped} captions skipped_"));
+                }
+                Err(broadcast::error::RecvError::Closed) => stopping = true,
+            },
+            _ = stop.changed(), if !stopping => stopping = true,
+            _ = sleep_until(next_flush) => {
+                if !pending.is_empty() {
+                    match flush(&config, &mut rolling, &mut pending).await {
+                        Ok(()) => backoff = config.flush_interval,
+                        Err(err) => {
+                            tracing::warn!(?err, channel = %config.target_channel, "Live caption post failed");
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                        }
+                    }
+                }
+                next_flush = Instant::now() + backoff;
+            }
+        }
+
+        if stopping {
+            if !pending.is_empty()
+                && let Err(err) = flush(&config, &mut rolling, &mut pending).await
+            {
+                tracing::warn!(?err, "Final live caption flush failed");
+            }
+            break;
+        }
+    }
+}
+
+async fn flush(
+    config: &LiveFeedConfig,
+    rolling: &mut RollingMessage,
+    pending: &mut Vec<String>,
+) -> serenity::Result<()> {
+    while !pending.is_empty() {
+        let mut next = rolling.content.clone();
+        let mut consumed = 0;
+        for line in pending.iter() {
+            let extra = line.chars().count() + 1;
+            if next.chars().count() + extra > MAX_MESSAGE_CHARS {
+                break;
+            }
+            next.push_str(line);
+            next.push('\n');
+            consumed += 1;
+        }
+
+        if consumed == 0 {
+            if rolling.id.is_some() {
+                // Current message is full; roll over to a fresh one.
+                rolling.id = None;
+                rolling.content.clear();
+                continue;
+            }
+            // A single line longer than a whole message: truncate it.
+            let line: String = pending[0].chars().take(MAX_MESSAGE_CHARS - 1).collect();
+            pending[0] = line;
+            continue;
+        }
+
+        match rolling.id {
+            Some(message_id) => {
+                config
+                    .target_channel
+                    .edit_message(
+                        &config.http,
+                        message_id,
+                        EditMessage::new()
+                            .content(&next)
+                            .allowed_mentions(CreateAllowedMentions::new()),
+                    )
+                    .await?;
+            }
+            None => {
+                let message = config
+                    .target_channel
+                    .send_message(
+                        &config.http,
+                        CreateMessage::new()
+                            .content(&next)
+                            .allowed_mentions(CreateAllowedMentions::new()),
+                    )
+                    .await?;
+                rolling.id = Some(message.id);
+            }
+        }
+        rolling.content = next;
+        pending.drain(..consumed);
+    }
+    Ok(())
+}
+
+fn render_entry(entry: &CaptionEntry, redactor: &Redactor) -> String {
+    let time = entry
+        .timestamp
+        .split_once('T')
+        .map(|(_, time)| time)
+        .unwrap_or(&entry.timestamp);
+    format!(
+        "`{}` **{}**: {}",
+        time,
+        escape_markdown(&entry.speaker.name),
+        escape_markdown(&redactor.export_text(&entry.comment))
+    )
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '*' | '_' | '~' | '`' | '|' | '>' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
@@ -1,15 +1,22 @@
 mod captions;
+mod commands;
 mod config;
+mod delivery;
+mod digest;
+mod live;
+mod redaction;
+mod retention;
 mod summaries;
 mod telemetry;
 mod transcription;
 mod utils;
 mod voice;
+mod webhooks;
 
 use std::{
     env,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex as StdMutex},
+    sync::{Arc, Mutex as StdMutex, OnceLock},
     time::Duration,
 };
 
@@ -20,13 +27,31 @@ use dotenvy::dotenv;
 use futures_util::StreamExt;
 use poise::{FrameworkOptions, builtins, serenity_prelude as serenity};
 use reqwest::Client as HttpClient;
-use tokio::{fs, io::AsyncWriteExt, process::Command, sync::oneshot, time::timeout};
+use tokio::{
+    fs,
+    io::AsyncWriteExt,
+    process::Command,
+    sync::{broadcast, mpsc, oneshot},
+    time::timeout,
+};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    captions::{CaptionSink, SessionSummary},
-    config::BotConfig,
-    summaries::OpenAiSummarizer,
+    captions::{
+        ActiveSession, CaptionEvent, CaptionSink, SessionSummary,
+        crypto::CaptionKeyring,
+        database::{TranscriptDatabase, TranscriptDbMode},
+        index::{SessionIndex, session_id_from_file_name},
+        storage::{SessionStore, StorageConfig},
+    },
+    config::{BotConfig, MIN_ROLLING_SUMMARY_MINUTES},
+    delivery::SessionDelivery,
+    live::{LiveCaptionFeed, LiveFeedConfig},
+    redaction::{RedactionMode, Redactor},
+    summaries::{
+        JobStatus, RollingSummaryConfig, RollingSummaryFeed, SummaryJob, SummaryOutput,
+        SummaryProgress, SummaryQueue, SummaryRequest, TranscriptSummarizer,
+    },
     telemetry::{AppMetrics, InviteTracker, spawn_http_server},
     transcription::{TranscriptionHandle, spawn_worker},
     utils::resolve_user_name,
@@ -34,6 +59,7 @@ use crate::{
         CaptionPipelineConfig, SpeakerUpdateReceiver, SpeakerUpdateSender, attach_caption_pipeline,
         roster::VoiceRoster, speaker_update_channel,
     },
+    webhooks::{WebhookDispatcher, WebhookEvent},
 };
 use serenity::{
     Client as DiscordClient,
@@ -60,6 +86,8 @@ type CallLock = Arc<tokio::sync::Mutex<Call>>;
 const WHISPER_CPP_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
 const INVITE_SCOPES: &str = "bot%20applications.commands";
 const ENTRY_SOUND_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often queued summary jobs are checked for a due retry.
+const SUMMARY_RETRY_POLL: Duration = Duration::from_secs(30);
 
 pub struct BotState {
     chunk_samples: usize,
@@ -68,15 +96,48 @@ pub struct BotState {
     transcriber: TranscriptionHandle,
     speaker_updates: SpeakerUpdateSender,
     caption_sink: Arc<CaptionSink>,
+    session_index: Arc<SessionIndex>,
     entry_sound_path: PathBuf,
     entry_sound_volume: f32,
-    summarizer: Option<OpenAiSummarizer>,
+    summarizer: Option<TranscriptSummarizer>,
+    summary_queue: Option<Arc<SummaryQueue>>,
     include_transcripts_with_summary: bool,
+    redactor: Arc<Redactor>,
+    webhooks: Arc<WebhookDispatcher>,
+    live_flush_interval: Duration,
+    rolling_summary_interval: Option<Duration>,
     active_calls: DashMap<GuildId, ChannelId>,
     voice_rosters: DashMap<GuildId, Arc<VoiceRoster>>,
+    live_feeds: DashMap<GuildId, LiveCaptionFeed>,
+    rolling_summaries: DashMap<GuildId, RollingSummaryFeed>,
+    discord: OnceLock<serenity::Context>,
     metrics: Arc<AppMetrics>,
 }
 
+/// Parameters for starting a caption session, shared by `/join` and the admin API.
+pub struct JoinRequest {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub title: Option<String>,
+    pub live_channel: Option<ChannelId>,
+    /// Summary template name; validated against the guild's templates before joining.
+    pub summary_template: Option<String>,
+    /// Text channel `/join` was run from; running notes are posted there.
+    pub text_channel: Option<ChannelId>,
+    /// Minutes between running notes; `Some(0)` turns them off and `None` uses
+    /// `SUMMARY_ROLLING_MINUTES`.
+    pub rolling_summary_minutes: Option<u32>,
+}
+
+/// A voice channel the bot is currently captioning.
+pub struct ActiveCall {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub participants: usize,
+    pub live_channel: Option<ChannelId>,
+    pub session: Option<ActiveSession>,
+}
+
 struct BotStateConfig {
     chunk_samples: usize,
     sample_rate: u32,
@@ -84,10 +145,16 @@ struct BotStateConfig {
     transcriber: TranscriptionHandle,
     speaker_updates: SpeakerUpdateSender,
     caption_sink: Arc<CaptionSink>,
+    session_index: Arc<SessionIndex>,
     entry_sound_path: PathBuf,
     entry_sound_volume: f32,
-    summarizer: Option<OpenAiSummarizer>,
+    summarizer: Option<TranscriptSummarizer>,
+    summary_queue: Option<Arc<SummaryQueue>>,
     include_transcripts_with_summary: bool,
+    redactor: Arc<Redactor>,
+    webhooks: Arc<WebhookDispatcher>,
+    live_flush_interval: Duration,
+    rolling_summary_interval: Option<Duration>,
     metrics: Arc<AppMetrics>,
 }
 
@@ -100,10 +167,16 @@ impl BotState {
             transcriber,
             speaker_updates,
             caption_sink,
+            session_index,
             entry_sound_path,
             entry_sound_volume,
             summarizer,
+            summary_queue,
             include_transcripts_with_summary,
+            redactor,
+            webhooks,
+            live_flush_interval,
+            rolling_summary_interval,
             metrics,
         } = config;
         Self {
@@ -113,12 +186,21 @@ impl BotState {
             transcriber,
             speaker_updates,
             caption_sink,
+            session_index,
             entry_sound_path,
             entry_sound_volume,
             summarizer,
+            summary_queue,
             include_transcripts_with_summary,
+            redactor,
+            webhooks,
+            live_flush_interval,
+            rolling_summary_interval,
             active_calls: DashMap::new(),
             voice_rosters: DashMap::new(),
+            live_feeds: DashMap::new(),
+            rolling_summaries: DashMap::new(),
+            discord: OnceLock::new(),
             metrics,
         }
     }
@@ -131,10 +213,43 @@ impl BotState {
         self.entry_sound_volume
     }
 
-    fn summarizer(&self) -> Option<OpenAiSummarizer> {
+    fn summarizer(&self) -> Option<TranscriptSummarizer> {
         self.summarizer.clone()
     }
 
+    fn summary_queue(&self) -> Option<Arc<SummaryQueue>> {
+        self.summary_queue.clone()
+    }
+
+    pub fn session_index(&self) -> Arc<SessionIndex> {
+        Arc::clone(&self.session_index)
+    }
+
+    pub fn transcriber(&self) -> &TranscriptionHandle {
+        &self.transcriber
+    }
+
+    pub fn caption_store(&self) -> Arc<dyn SessionStore> {
+        Arc::clone(self.caption_sink.store())
+    }
+
+    fn set_discord_context(&self, ctx: serenity::Context) {
+        let _ = self.discord.set(ctx);
+    }
+
+    /// Gateway context captured when the bot first became ready; `None` before that.
+    pub fn discord_context(&self) -> Option<serenity::Context> {
+        self.discord.get().cloned()
+    }
+
+    pub fn subscribe_captions(&self) -> broadcast::Receiver<CaptionEvent> {
+        self.caption_sink.subscribe()
+    }
+
+    pub fn redactor(&self) -> Arc<Redactor> {
+        Arc::clone(&self.redactor)
+    }
+
     fn include_transcripts_with_summary(&self) -> bool {
         self.include_transcripts_with_summary
     }
@@ -156,6 +271,404 @@ impl BotState {
             .map(|(_, channel)| channel)
     }
 
+    pub fn active_calls(&self) -> Vec<ActiveCall> {
+        let mut calls: Vec<_> = self
+            .active_calls
+            .iter()
+            .map(|entry| {
+                let (guild_id, channel_id) = (*entry.key(), *entry.value());
+                ActiveCall {
+                    guild_id,
+                    channel_id,
+                    participants: self
+                        .voice_rosters
+                        .get(&guild_id)
+                        .map(|roster| roster.value().participant_count())
+                        .unwrap_or_default(),
+                    live_channel: self
+                        .live_feeds
+                        .get(&guild_id)
+                        .map(|feed| feed.value().target_channel()),
+                    session: self.caption_sink.active_session(guild_id, channel_id),
+                }
+            })
+            .collect();
+        calls.sort_by_key(|call| call.guild_id);
+        calls
+    }
+
+    /// Spacing of the running notes posted for the call in `guild_id`, if any.
+    fn rolling_summary_interval(&self, guild_id: GuildId) -> Option<Duration> {
+        self.rolling_summaries
+            .get(&guild_id)
+            .map(|feed| feed.value().interval())
+    }
+
+    /// The session being recorded in `guild_id`, if the bot is in a call there.
+    fn active_session(&self, guild_id: GuildId) -> Option<ActiveSession> {
+        let channel_id = *self.active_calls.get(&guild_id)?.value();
+        self.caption_sink.active_session(guild_id, channel_id)
+    }
+
+    /// Retitles the running session in `guild_id`. Returns `false` if nothing is recording.
+    pub async fn retitle_session(
+        &self,
+        guild_id: GuildId,
+        title: Option<String>,
+    ) -> anyhow::Result<bool> {
+        let Some(channel_id) = self.active_calls.get(&guild_id).map(|entry| *entry.value()) else {
+            return Ok(false);
+        };
+        let sink = Arc::clone(&self.caption_sink);
+        tokio::task::spawn_blocking(move || sink.retitle_session(guild_id, channel_id, title))
+            .await?
+    }
+
+    /// Joins a voice channel, arms the caption pipeline, and opens a session file.
+    /// Errors carry a user-facing message.
+    pub async fn join_voice(
+        &self,
+        ctx: &serenity::Context,
+        request: JoinRequest,
+    ) -> anyhow::Result<()> {
+        let JoinRequest {
+            guild_id,
+            channel_id,
+            title,
+            live_channel,
+            summary_template,
+            text_channel,
+            rolling_summary_minutes,
+        } = request;
+        let summary_template = match summary_template
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            Some(requested) => {
+                let Some(summarizer) = self.summarizer() else {
+                    bail!("Summaries are disabled on this bot, so a template cannot be chosen");
+                };
+                let (name, _) = summarizer
+                    .templates()
+                    .resolve(Some(guild_id), Some(requested))?;
+                Some(name.to_string())
+            }
+            None => None,
+        };
+        let rolling_interval = match rolling_summary_minutes {
+            Some(0) => None,
+            Some(minutes) => Some(Duration::from_secs(
+                u64::from(minutes).max(MIN_ROLLING_SUMMARY_MINUTES) * 60,
+            )),
+            None => self.rolling_summary_interval,
+        };
+        if rolling_summary_minutes.is_some_and(|minutes| minutes > 0) && self.summarizer.is_none() {
+            bail!("Summaries are disabled on this bot, so running notes are unavailable");
+        }
+        let manager = songbird::get(ctx)
+            .await
+            .ok_or_else(|| anyhow!("Voice client not initialised"))?;
+
+        let handler_lock = manager
+            .join(guild_id, channel_id)
+            .await
+            .map_err(|err| anyhow!("Failed to join: {err:?}"))?;
+
+        if let Err(err) = play_entry_sound(
+            &handler_lock,
+            &self.entry_sound_path,
+            self.entry_sound_volume(),
+        )
+        .await
+        {
+            tracing::warn!(?err, "Entry sound playback failed");
+        }
+        if let Err(err) = self_mute_call(&handler_lock).await {
+            tracing::warn!(?err, "Failed to self-mute after joining");
+        }
+
+        let roster = self.prepare_roster(ctx, guild_id, channel_id).await;
+
+        attach_caption_pipeline(
+            &handler_lock,
+            CaptionPipelineConfig {
+                guild_id,
+                channel_id,
+                chunk_samples: self.chunk_samples,
+                sample_rate: self.sample_rate,
+                transcriber: self.transcriber.clone(),
+                speaker_updates: Some(self.speaker_updates()),
+                ctx: ctx.clone(),
+                caption_sink: self.caption_sink.clone(),
+                silence_flush: self.chunk_duration,
+                roster,
+            },
+        )
+        .await
+        .map_err(|err| anyhow!("Failed to arm caption pipeline: {err:?}"))?;
+
+        self.track_call(guild_id, channel_id);
+        let sink = Arc::clone(&self.caption_sink);
+        let session_title = title.clone();
+        let started = tokio::task::spawn_blocking(move || {
+            sink.start_session(
+                guild_id,
+                channel_id,
+                session_title,
+                summary_template,
+                text_channel,
+            )
+        })
+        .await;
+        let session_file = match started.map_err(anyhow::Error::from) {
+            Ok(Ok(file_name)) => file_name,
+            Ok(Err(err)) | Err(err) => {
+                tracing::error!(?err, "Failed to initialise caption session file");
+                bail!("Joined, but failed to prepare the caption log on disk");
+            }
+        };
+        self.webhooks.session_started(
+            guild_id,
+            channel_id,
+            &session_id_from_file_name(&session_file)
+                .map(|(id, _)| id)
+                .unwrap_or_default(),
+            title.as_deref(),
+        );
+        self.metrics.record_session_started();
+        if let Some(live_channel) = live_channel {
+            self.start_live_feed(ctx, guild_id, channel_id, live_channel)
+                .await;
+        }
+        if let (Some(interval), Some(text_channel), Some(summarizer)) =
+            (rolling_interval, text_channel, self.summarizer())
+        {
+            self.start_rolling_summary(
+                ctx,
+                guild_id,
+                channel_id,
+                text_channel,
+                interval,
+                summarizer,
+            )
+            .await;
+        }
+        Ok(())
+    }
+
+    /// Leaves voice in `guild_id` and finalizes the caption session, returning its summary
+    /// when one was recorded. Errors carry a user-facing message.
+    pub async fn leave_voice(
+        &self,
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+    ) -> anyhow::Result<Option<SessionSummary>> {
+        let manager = songbird::get(ctx)
+            .await
+            .ok_or_else(|| anyhow!("Voice client not initialised"))?;
+        // Untracked first, so the bot's own voice state update is not taken for a kick.
+        let channel = self.take_call_channel(guild_id);
+        match manager.remove(guild_id).await {
+            Ok(()) | Err(songbird::error::JoinError::NoCall) => {}
+            Err(err) => {
+                if let Some(channel) = channel {
+                    self.track_call(guild_id, channel);
+                }
+                bail!("Failed to leave: {err}");
+            }
+        }
+
+        self.stop_live_feed(guild_id).await;
+        self.stop_rolling_summary(guild_id).await;
+        self.speaker_updates.clear();
+        self.clear_roster(guild_id).await;
+        let Some(channel) = channel else {
+            return Ok(None);
+        };
+        self.metrics.record_session_completed();
+        let sink = Arc::clone(&self.caption_sink);
+        let ended = tokio::task::spawn_blocking(move || sink.end_session(guild_id, channel)).await;
+        match ended.map_err(anyhow::Error::from) {
+            Ok(Ok(summary)) => {
+                if let Some(summary) = summary.as_ref() {
+                    self.notify_session_ended(guild_id, channel, summary).await;
+                }
+                Ok(summary)
+            }
+            Ok(Err(err)) | Err(err) => {
+                tracing::error!(?err, "Failed to finalize caption session");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Leaves without an interaction to answer (the bot was disconnected, or the admin
+    /// API or shutdown asked) and delivers the results in the background to the channel
+    /// `/join` was run from.
+    pub async fn leave_unattended(
+        self: &Arc<Self>,
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+        reason: &'static str,
+    ) -> anyhow::Result<Option<SessionSummary>> {
+        let summary = self.leave_voice(ctx, guild_id).await?;
+        if let Some(summary) = summary.clone() {
+            tracing::info!(guild = %guild_id, session = %summary.session_id(), reason, "Session ended without /leave");
+            let state = Arc::clone(self);
+            let http = Arc::clone(&ctx.http);
+            tokio::spawn(async move {
+                let Some(channel) = summary.text_channel else {
+                    tracing::info!(
+                        session = %summary.session_id(),
+                        "No text channel recorded for session; results are stored but not posted"
+                    );
+                    return;
+                };
+                SessionDelivery::new(&state, &http, guild_id, channel)
+                    .deliver(&summary)
+                    .await;
+            });
+        }
+        Ok(summary)
+    }
+
+    /// Ends every call before the process exits. Transcripts are uploaded, and summaries
+    /// are queued for the worker to post after the next start instead of holding up
+    /// shutdown.
+    async fn shutdown_calls(&self, ctx: &serenity::Context) {
+        let guilds: Vec<GuildId> = self.active_calls.iter().map(|entry| *entry.key()).collect();
+        for guild_id in guilds {
+            let summary = match self.leave_voice(ctx, guild_id).await {
+                Ok(Some(summary)) => summary,
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::warn!(?err, guild = %guild_id, "Failed to leave voice on shutdown");
+                    continue;
+                }
+            };
+            let Some(channel) = summary.text_channel else {
+                continue;
+            };
+            let delivery = SessionDelivery::new(self, &ctx.http, guild_id, channel);
+            delivery.upload_transcript(&summary).await;
+            // Claims are not persisted, so the job is pending again after restart.
+            delivery.enqueue_summary(&summary);
+        }
+    }
+
+    /// Makes one attempt at a claimed summary job: summarizes the session, stores the
+    /// result beside it, and announces it to webhooks. Failures are logged and recorded
+    /// on the job, which is retried with backoff until it runs out of attempts.
+    async fn run_summary_job(
+        &self,
+        job: &SummaryJob,
+        progress: Option<mpsc::UnboundedSender<SummaryProgress>>,
+    ) -> anyhow::Result<SummaryOutput> {
+        let (Some(summarizer), Some(queue)) = (self.summarizer(), self.summary_queue()) else {
+            bail!("Summaries are disabled");
+        };
+        let sink = Arc::clone(&self.caption_sink);
+        let file_name = job.file_name.clone();
+        let result =
+            match tokio::task::spawn_blocking(move || sink.read_session_bytes(&file_name)).await {
+                Ok(Ok(transcript)) => {
+                    let request = SummaryRequest {
+                        label: &job.label,
+                        guild_id: Some(job.guild_id),
+                        template: None,
+                    };
+                    summarizer
+                        .summarize_with_progress(&transcript, &request, progress)
+                        .await
+                }
+                Ok(Err(err)) => Err(err.context("reading caption file for summary")),
+                Err(err) => Err(err.into()),
+            };
+
+        match &result {
+            Ok(output) => {
+                commands::store_summary(
+                    Arc::clone(&self.caption_sink),
+                    job.file_name.clone(),
+                    output,
+                )
+                .await;
+                self.webhooks.summary_ready(
+                    job.guild_id,
+                    &job.session_id,
+                    job.title.as_deref(),
+                    output,
+                );
+                if let Err(err) = queue.complete(&job.session_id) {
+                    tracing::warn!(?err, session = %job.session_id, "Failed to persist summary job");
+                }
+            }
+            Err(err) => match queue.fail(&job.session_id, err) {
+                Ok(Some(updated)) if updated.status == JobStatus::Failed => tracing::error!(
+                    ?err,
+                    provider = summarizer.provider(),
+                    session = %job.session_id,
+                    attempts = updated.attempts,
+                    "Transcript summary failed; giving up"
+                ),
+                Ok(Some(updated)) => tracing::warn!(
+                    ?err,
+                    provider = summarizer.provider(),
+                    session = %job.session_id,
+                    attempts = updated.attempts,
+                    next_attempt_at = %updated.next_attempt_at,
+                    "Transcript summary failed; will retry"
+                ),
+                Ok(None) => {}
+                Err(persist_err) => {
+                    tracing::error!(?err, ?persist_err, session = %job.session_id, "Transcript summary failed and its job could not be persisted")
+                }
+            },
+        }
+        result
+    }
+
+    /// Loads and redacts the session document off the runtime, and only when the
+    /// `session.ended` webhook is subscribed and embeds it.
+    async fn notify_session_ended(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        summary: &SessionSummary,
+    ) {
+        if !self.webhooks.wants(WebhookEvent::SessionEnded) {
+            return;
+        }
+        let document = if self.webhooks.includes_document() {
+            let sink = Arc::clone(&self.caption_sink);
+            let redactor = Arc::clone(&self.redactor);
+            let file_name = summary.file_name.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                let mut document = sink.read_session_document(&file_name)?;
+                redactor.redact_document_for_export(&mut document);
+                anyhow::Ok(document)
+            })
+            .await;
+            match result.map_err(anyhow::Error::from) {
+                Ok(Ok(document)) => Some(document),
+                Ok(Err(err)) | Err(err) => {
+                    tracing::warn!(?err, "Failed reading caption file for webhook");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        self.webhooks.session_ended(
+            guild_id,
+            channel_id,
+            &summary.session_id(),
+            summary,
+            document.as_ref(),
+        );
+    }
+
     pub fn connected_guilds(&self) -> usize {
         self.active_calls.len()
     }
@@ -203,6 +716,62 @@ impl BotState {
         roster
     }
 
+    async fn start_live_feed(
+        &self,
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+        voice_channel: ChannelId,
+        target_channel: ChannelId,
+    ) {
+        self.stop_live_feed(guild_id).await;
+        let feed = LiveCaptionFeed::spawn(
+            &self.caption_sink,
+            LiveFeedConfig {
+                http: Arc::clone(&ctx.http),
+                guild_id,
+                voice_channel,
+                target_channel,
+                flush_interval: self.live_flush_interval,
+                redactor: Arc::clone(&self.redactor),
+            },
+        );
+        self.live_feeds.insert(guild_id, feed);
+    }
+
+    async fn stop_live_feed(&self, guild_id: GuildId) {
+        if let Some((_, feed)) = self.live_feeds.remove(&guild_id) {
+            feed.stop().await;
+        }
+    }
+
+    async fn start_rolling_summary(
+        &self,
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+        voice_channel: ChannelId,
+        target_channel: ChannelId,
+        interval: Duration,
+        summarizer: TranscriptSummarizer,
+    ) {
+        self.stop_rolling_summary(guild_id).await;
+        let feed = RollingSummaryFeed::spawn(RollingSummaryConfig {
+            http: Arc::clone(&ctx.http),
+            guild_id,
+            voice_channel,
+            target_channel,
+            interval,
+            summarizer,
+            sink: Arc::clone(&self.caption_sink),
+        });
+        self.rolling_summaries.insert(guild_id, feed);
+    }
+
+    async fn stop_rolling_summary(&self, guild_id: GuildId) {
+        if let Some((_, feed)) = self.rolling_summaries.remove(&guild_id) {
+            feed.stop().await;
+        }
+    }
+
     async fn clear_roster(&self, guild_id: GuildId) {
         if let Some(roster) = self.voice_rosters.get(&guild_id) {
             roster.value().clone().clear().await;
@@ -210,7 +779,7 @@ impl BotState {
     }
 
     async fn handle_voice_state_update(
-        &self,
+        self: &Arc<Self>,
         ctx: &serenity::Context,
         old: Option<&serenity::model::prelude::VoiceState>,
         new: &serenity::model::prelude::VoiceState,
@@ -228,6 +797,15 @@ impl BotState {
 
         let bot_id = ctx.cache.current_user().id;
         if new.user_id == bot_id {
+            if new.channel_id.is_none() {
+                let state = Arc::clone(self);
+                let ctx = ctx.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = state.leave_unattended(&ctx, guild_id, "disconnected").await {
+                        tracing::warn!(?err, guild = %guild_id, "Failed to end session after disconnect");
+                    }
+                });
+            }
             return;
         }
 
@@ -263,6 +841,10 @@ async fn main() -> anyhow::Result<()> {
         .with(fmt::layer())
         .init();
 
+    if let Some(command) = env::args().nth(1) {
+        return run_cli_command(&command).await;
+    }
+
     let config = BotConfig::from_env()?;
     // Ensure captions folder exists on startup
     if let Err(e) = std::fs::create_dir_all(&config.caption_dir) {
@@ -273,31 +855,107 @@ async fn main() -> anyhow::Result<()> {
     let (speaker_updates, speaker_rx) = speaker_update_channel();
     let speaker_rx = Arc::new(StdMutex::new(Some(speaker_rx)));
     ensure_model_available(&config).await?;
-    let caption_sink = Arc::new(CaptionSink::new(config.caption_dir.clone()));
+    let keyring = CaptionKeyring::from_env()?.map(Arc::new);
+    if let Some(keyring) = &keyring {
+        tracing::info!(
+            key_id = keyring.current_key_id(),
+            "Caption files will be encrypted at rest"
+        );
+    }
+    let caption_store = config.storage.open()?;
+    tracing::info!(location = %caption_store.location(), "Caption storage ready");
+    if matches!(config.storage, StorageConfig::S3(_)) {
+        // Only session documents move to the bucket; these files stay on local disk.
+        let local_state: Vec<String> = [
+            config.summary.as_ref().map(|summary| &summary.queue.path),
+            config
+                .digest
+                .is_enabled()
+                .then_some(&config.digest.state_path),
+            (!config.webhooks.endpoints.is_empty()).then_some(&config.webhooks.dead_letter_path),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|path| path.display().to_string())
+        .collect();
+        if !local_state.is_empty() {
+            tracing::warn!(
+                files = ?local_state,
+                "S3 storage keeps summary jobs, digest state, and webhook dead letters on local disk; mount a persistent volume for them"
+            );
+        }
+    }
+    let mut caption_sink = CaptionSink::new(caption_store, keyring.clone());
+    if let Some(db_config) = &config.transcript_db {
+        let database = TranscriptDatabase::open(&db_config.path)?.with_keyring(keyring.clone());
+        tracing::info!(
+            path = %db_config.path.display(),
+            mode = db_config.mode.as_str(),
+            "Transcript database enabled"
+        );
+        caption_sink = caption_sink.with_database(
+            Arc::new(database),
+            db_config.mode == TranscriptDbMode::Mirror,
+        );
+    }
+    let caption_sink = Arc::new(caption_sink);
+    captions::spawn_draft_flusher(Arc::clone(&caption_sink));
+    let redactor = Arc::new(Redactor::new(&config.redaction)?);
+    if redactor.mode() != RedactionMode::Off {
+        tracing::info!(
+            mode = redactor.mode().as_str(),
+            "PII redaction enabled for transcripts"
+        );
+    }
     let transcriber = spawn_worker(
         config.whisper_model_path.clone(),
         caption_sink.clone(),
         config.whisper_language.clone(),
         config.whisper_use_gpu,
         config.whisper_gpu_device,
+        Arc::clone(&redactor),
         Arc::clone(&metrics),
     )?;
     let summarizer = config
-        .openai_api_key
+        .summary
+        .as_ref()
+        .map(|summary| summary.build(Arc::clone(&redactor)));
+    let summary_queue = config
+        .summary
         .as_ref()
-        .map(|key| OpenAiSummarizer::new(key.clone(), config.openai_model.clone()));
-    if summarizer.is_some() {
+        .map(|summary| SummaryQueue::open(summary.queue.clone()).map(Arc::new))
+        .transpose()?;
+    if let Some(queue) = &summary_queue {
+        tracing::info!(
+            path = %queue.path().display(),
+            pending = queue.pending(),
+            "Summary job queue ready"
+        );
+    }
+    if let Some(summary) = config.summary.as_ref() {
         let transcript_policy = if config.include_transcripts_with_summary {
             "will"
         } else {
             "will not"
         };
         tracing::info!(
+            provider = summary.provider.as_str(),
+            base_url = %summary.base_url,
+            model = %summary.model,
+            templates = %summary.templates.names().collect::<Vec<_>>().join(","),
             include_transcripts_with_summary = config.include_transcripts_with_summary,
-            "OpenAI summaries enabled; transcripts {transcript_policy} accompany summaries"
+            "Summaries enabled; transcripts {transcript_policy} accompany summaries"
         );
     } else {
-        tracing::info!("OpenAI summaries disabled (OPENAPI_KEY not set)");
+        tracing::info!("Summaries disabled (no SUMMARY_API_KEY or SUMMARY_BASE_URL set)");
+    }
+    let webhooks = Arc::new(WebhookDispatcher::new(config.webhooks.clone()).with_keyring(keyring));
+    if webhooks.is_enabled() {
+        tracing::info!(
+            endpoints = config.webhooks.endpoints.len(),
+            signed = config.webhooks.secret.is_some(),
+            "Outbound webhooks enabled"
+        );
     }
     let data = Arc::new(BotState::new(BotStateConfig {
         chunk_samples: config.chunk_samples(),
@@ -305,19 +963,60 @@ async fn main() -> anyhow::Result<()> {
         chunk_duration: config.chunk_duration,
         transcriber,
         speaker_updates: speaker_updates.clone(),
+        session_index: Arc::new(
+            SessionIndex::new(Arc::clone(&caption_sink), Arc::clone(&redactor))
+                .with_public_base_url(config.webhooks.public_base_url.clone()),
+        ),
         caption_sink,
         entry_sound_path: config.entry_sound_path.clone(),
         entry_sound_volume: config.entry_sound_volume,
         summarizer,
+        summary_queue: summary_queue.clone(),
         include_transcripts_with_summary: config.include_transcripts_with_summary,
+        redactor,
+        webhooks,
+        live_flush_interval: config.live_caption_flush_interval,
+        rolling_summary_interval: config
+            .summary
+            .as_ref()
+            .and_then(|summary| summary.rolling_interval),
         metrics: Arc::clone(&metrics),
     }));
 
+    if config.retention.is_enabled() {
+        tracing::info!(
+            action = config.retention.action.as_str(),
+            interval_secs = config.retention.interval.as_secs(),
+            "Caption retention policy enabled"
+        );
+        retention::spawn_retention_task(
+            config.retention.clone(),
+            Arc::clone(&data.caption_sink),
+            Arc::clone(&metrics),
+        );
+    }
+
+    if let Some(queue) = summary_queue {
+        tokio::spawn(run_summary_worker(Arc::clone(&data), queue));
+    }
+
+    if let (true, Some(summarizer)) = (config.digest.is_enabled(), data.summarizer()) {
+        tracing::info!(
+            schedules = config.digest.schedules.len(),
+            hour = config.digest.hour,
+            weekday = %config.digest.weekday,
+            "Session digests enabled"
+        );
+        digest::spawn_digest_task(config.digest.clone(), Arc::clone(&data), summarizer);
+    }
+
     let _http_server = spawn_http_server(
         config.http_bind_addr,
         Arc::clone(&data),
         metrics,
         invite_tracker.clone(),
+        config.http_api_token.clone(),
+        config.transcription_stall_threshold,
     )?;
 
     let intents = GatewayIntents::GUILDS
@@ -331,13 +1030,33 @@ async fn main() -> anyhow::Result<()> {
         .decode_sample_rate(sample_rate_from(config.sample_rate))
         .decode_channels(DecodeChannels::Mono);
 
+    let data_for_shutdown = Arc::clone(&data);
     let framework = poise::Framework::builder()
         .options(FrameworkOptions {
-            commands: vec![join(), leave(), ping()],
+            commands: vec![
+                join(),
+                leave(),
+                ping(),
+                commands::search(),
+                commands::ask(),
+                commands::sessions(),
+                commands::summarize(),
+            ],
             event_handler: |ctx, event, _framework, data| {
                 Box::pin(async move {
-                    if let serenity::FullEvent::VoiceStateUpdate { old, new } = event {
-                        data.handle_voice_state_update(ctx, old.as_ref(), new).await;
+                    match event {
+                        serenity::FullEvent::VoiceStateUpdate { old, new } => {
+                            data.handle_voice_state_update(ctx, old.as_ref(), new).await;
+                        }
+                        serenity::FullEvent::Ready { .. } | serenity::FullEvent::Resume { .. } => {
+                            data.metrics.set_gateway_connected(true);
+                        }
+                        serenity::FullEvent::ShardStageUpdate { event } => {
+                            data.metrics.set_gateway_connected(
+                                event.new == serenity::ConnectionStage::Connected,
+                            );
+                        }
+                        _ => {}
                     }
                     Ok(())
                 })
@@ -350,6 +1069,8 @@ async fn main() -> anyhow::Result<()> {
             let invite_tracker = invite_tracker.clone();
             Box::pin(async move {
                 tracing::info!("{} is connected", ready.user.name);
+                data.set_discord_context(ctx.clone());
+                data.metrics.set_gateway_connected(true);
                 if let Some(rx) = speaker_rx.lock().unwrap().take() {
                     tokio::spawn(run_presence_task(ctx.clone(), rx));
                 }
@@ -368,11 +1089,116 @@ async fn main() -> anyhow::Result<()> {
         .await
         .context("creating Discord client")?;
 
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        tracing::info!("Shutdown requested; ending active sessions");
+        if let Some(ctx) = data_for_shutdown.discord_context() {
+            data_for_shutdown.shutdown_calls(&ctx).await;
+        }
+        shard_manager.shutdown_all().await;
+    });
+
     client.start().await.context("Discord client shutdown")?;
 
     Ok(())
 }
 
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(err) => tracing::warn!(?err, "Failed to listen for SIGTERM"),
+        }
+    }
+    if let Err(err) = tokio::signal::ctrl_c().await {
+        tracing::warn!(?err, "Failed to listen for Ctrl-C; shutdown hook disabled");
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn run_cli_command(command: &str) -> anyhow::Result<()> {
+    match command {
+        "rotate-caption-keys" => {
+            let keyring = CaptionKeyring::from_env()?.ok_or_else(|| {
+                anyhow!("rotate-caption-keys requires CAPTION_ENCRYPTION_KEY or CAPTION_ENCRYPTION_KEY_FILE")
+            })?;
+            let caption_dir = env::var("CAPTION_OUTPUT_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("captions"));
+            let store = BotConfig::storage_from_env(&caption_dir)?.open()?;
+            let report = captions::crypto::rotate_store(store.as_ref(), &keyring)?;
+            tracing::info!(
+                key_id = keyring.current_key_id(),
+                rewrapped = report.rewrapped,
+                encrypted = report.encrypted,
+                unchanged = report.unchanged,
+                failed = report.failed,
+                "Caption key rotation finished"
+            );
+            if report.failed > 0 {
+                bail!("{} caption files could not be rotated", report.failed);
+            }
+            Ok(())
+        }
+        "import-sessions" => {
+            let db_config = BotConfig::transcript_db_from_env()?
+                .ok_or_else(|| anyhow!("import-sessions requires TRANSCRIPT_DB_PATH"))?;
+            let caption_dir = env::var("CAPTION_OUTPUT_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("captions"));
+            let store = BotConfig::storage_from_env(&caption_dir)?.open()?;
+            let keyring = CaptionKeyring::from_env()?.map(Arc::new);
+            let sink = CaptionSink::new(store, keyring.clone());
+            let database = TranscriptDatabase::open(&db_config.path)?.with_keyring(keyring);
+            let replace = env::args().skip(2).any(|arg| arg == "--replace");
+            let report = database.import_from(&sink, replace)?;
+            tracing::info!(
+                imported = report.imported,
+                skipped = report.skipped,
+                failed = report.failed,
+                "Session import finished"
+            );
+            if report.failed > 0 {
+                bail!("{} session documents could not be imported", report.failed);
+            }
+            Ok(())
+        }
+        "send-test-webhook" => {
+            let caption_dir = env::var("CAPTION_OUTPUT_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("captions"));
+            let config = BotConfig::webhooks_from_env(&caption_dir)?;
+            let endpoints = config.endpoints.len();
+            if endpoints == 0 {
+                bail!("send-test-webhook requires WEBHOOK_URLS");
+            }
+            let delivered = WebhookDispatcher::new(config).ping().await;
+            tracing::info!(delivered, endpoints, "Test webhook finished");
+            if delivered < endpoints {
+                bail!(
+                    "{} webhook endpoints did not accept the test event",
+                    endpoints - delivered
+                );
+            }
+            Ok(())
+        }
+        other => {
+            bail!(
+                "Unknown command '{other}' (available: rotate-caption-keys, import-sessions, send-test-webhook)"
+            )
+        }
+    }
+}
+
 #[poise::command(slash_command, guild_only)]
 async fn join(
     ctx: BotContext<'_>,
@@ -380,6 +1206,15 @@ async fn join(
         ChannelId,
     >,
     #[description = "Optional title for the generated notes"] title: Option<String>,
+    #[description = "Post live captions to a text channel while listening"] live: Option<bool>,
+    #[description = "Text channel for live captions (defaults to the voice channel's chat)"]
+    live_channel: Option<ChannelId>,
+    #[description = "Summary template used when the session ends"]
+    #[autocomplete = "commands::autocomplete_template"]
+    template: Option<String>,
+    #[description = "Post running notes here every N minutes (0 turns them off)"]
+    #[max = 240]
+    summary_every: Option<u32>,
 ) -> Result<(), Error> {
     let Some(guild_id) = ctx.guild_id() else {
         return Ok(());
@@ -408,72 +1243,44 @@ async fn join(
         return Ok(());
     };
 
-    let Some(manager) = songbird::get(ctx.serenity_context()).await else {
-        ctx.say("Voice client not initialised").await?;
-        return Ok(());
-    };
-    let manager = manager.clone();
-
-    let handler_lock = match manager.join(guild_id, target_channel).await {
-        Ok(lock) => lock,
-        Err(err) => {
-            ctx.say(format!("Failed to join: {err:?}")).await?;
-            return Ok(());
-        }
+    let state = Arc::clone(ctx.data());
+    let live_target = (live.unwrap_or(false) || live_channel.is_some())
+        .then(|| live_channel.unwrap_or(target_channel));
+    let request = JoinRequest {
+        guild_id,
+        channel_id: target_channel,
+        title: session_title.clone(),
+        live_channel: live_target,
+        summary_template: template.clone(),
+        text_channel: Some(ctx.channel_id()),
+        rolling_summary_minutes: summary_every,
     };
+    if let Err(err) = state.join_voice(ctx.serenity_context(), request).await {
+        ctx.say(err.to_string()).await?;
+        return Ok(());
+    }
 
-    let state = Arc::clone(ctx.data());
-    let entry_sound_path = state.entry_sound_path.clone();
-    let entry_sound_volume = state.entry_sound_volume();
-    if let Err(err) = play_entry_sound(&handler_lock, &entry_sound_path, entry_sound_volume).await {
-        tracing::warn!(?err, "Entry sound playback failed");
+    let mut response = format!("Listening in {}", target_channel.mention());
+    if let Some(title) = session_title.as_ref() {
+        response.push_str(&format!(" — notes titled \"{}\"", title));
     }
-    if let Err(err) = self_mute_call(&handler_lock).await {
-        tracing::warn!(?err, "Failed to self-mute after joining");
+    if let Some(live_target) = live_target {
+        response.push_str(&format!(" — live captions in {}", live_target.mention()));
     }
-
-    let roster = state
-        .prepare_roster(ctx.serenity_context(), guild_id, target_channel)
-        .await;
-
-    if let Err(err) = attach_caption_pipeline(
-        &handler_lock,
-        CaptionPipelineConfig {
-            guild_id,
-            channel_id: target_channel,
-            chunk_samples: state.chunk_samples,
-            sample_rate: state.sample_rate,
-            transcriber: state.transcriber.clone(),
-            speaker_updates: Some(state.speaker_updates()),
-            ctx: ctx.serenity_context().clone(),
-            caption_sink: state.caption_sink.clone(),
-            silence_flush: state.chunk_duration,
-            roster,
-        },
-    )
-    .await
+    if let Some(template) = template
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
     {
-        ctx.say(format!("Failed to arm caption pipeline: {err:?}"))
-            .await?;
-    } else {
-        state.track_call(guild_id, target_channel);
-        if let Err(err) =
-            state
-                .caption_sink
-                .start_session(guild_id, target_channel, session_title.clone())
-        {
-            tracing::error!(?err, "Failed to initialise caption session file");
-            ctx.say("Joined, but failed to prepare the caption log on disk")
-                .await?;
-        } else {
-            let mut response = format!("Listening in {}", target_channel.mention());
-            state.metrics.record_session_started();
-            if let Some(title) = session_title.as_ref() {
-                response.push_str(&format!(" — notes titled \"{}\"", title));
-            }
-            ctx.say(response).await?;
-        }
+        response.push_str(&format!(" — `{}` summary", template.to_ascii_lowercase()));
+    }
+    if let Some(interval) = state.rolling_summary_interval(guild_id) {
+        response.push_str(&format!(
+            " — running notes every {} min",
+            interval.as_secs() / 60
+        ));
     }
+    ctx.say(response).await?;
 
     Ok(())
 }
@@ -555,84 +1362,27 @@ async fn leave(ctx: BotContext<'_>) -> Result<(), Error> {
     ctx.defer().await?;
 
     let state = Arc::clone(ctx.data());
-    let Some(manager) = songbird::get(ctx.serenity_context()).await else {
-        ctx.say("Voice client not initialised").await?;
-        return Ok(());
-    };
-    let manager = manager.clone();
-
-    match manager.remove(guild_id).await {
-        Ok(_) => {
-            ctx.say("Left voice channel").await?;
-            state.speaker_updates.clear();
-            state.clear_roster(guild_id).await;
-            let (transcript_summary, had_session) =
-                if let Some(channel) = state.take_call_channel(guild_id) {
-                    let summary = match state.caption_sink.end_session(guild_id, channel) {
-                        Ok(summary) => summary,
-                        Err(err) => {
-                            tracing::error!(?err, "Failed to finalize caption session");
-                            None
-                        }
-                    };
-                    (summary, true)
-                } else {
-                    (None, false)
-                };
-
-            if had_session {
-                state.metrics.record_session_completed();
-            }
-
-            if let Some(summary) = transcript_summary {
-                let label = transcript_label(&summary);
-                let summarizer = state.summarizer();
-                let should_upload_transcript =
-                    summarizer.is_none() || state.include_transcripts_with_summary();
-
-                if should_upload_transcript {
-                    match std::fs::read_to_string(&summary.file_path) {
-                        Ok(contents) => {
-                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&contents) {
-                                let minified = serde_json::to_string(&json).unwrap_or(contents);
-                                use poise::{CreateReply, serenity_prelude::CreateAttachment};
-                                let filename = format!("{}.json", label);
-                                let message = format!("{} ({})", label, summary.duration_hms());
-                                ctx.send(CreateReply::default().content(message).attachment(
-                                    CreateAttachment::bytes(minified.into_bytes(), filename),
-                                ))
-                                .await?;
-                            } else {
-                                tracing::warn!("Failed to parse caption JSON before upload");
-                            }
-                        }
-                        Err(err) => {
-                            tracing::error!(?err, "Failed reading caption file for upload")
-                        }
-                    }
-                } else {
-                    tracing::info!(
-                        %label,
-                        "Skipping transcript upload because INCLUDE_TRANSCRIPTS_WITH_SUMMARY is disabled"
-                    );
-                }
-
-                if let Some(summarizer) = summarizer {
-                    match summarizer
-                        .summarize_transcript(&summary.file_path, &label)
-                        .await
-                    {
-                        Ok(text) => {
-                            let content = format!("Summary for {}:\n{}", label, text);
-                            ctx.say(content).await?;
-                        }
-                        Err(err) => tracing::error!(?err, "OpenAI transcript summary failed"),
-                    }
-                }
+    match state.leave_voice(ctx.serenity_context(), guild_id).await {
+        Ok(Some(summary)) => {
+            let channel = summary.text_channel.unwrap_or_else(|| ctx.channel_id());
+            if channel == ctx.channel_id() {
+                ctx.say("Left voice channel").await?;
+            } else {
+                ctx.say(format!(
+                    "Left voice channel; the transcript and summary are posted in {}",
+                    channel.mention()
+                ))
+                .await?;
             }
+            SessionDelivery::new(&state, &ctx.serenity_context().http, guild_id, channel)
+                .deliver(&summary)
+                .await;
+        }
+        Ok(None) => {
+            ctx.say("Left voice channel").await?;
         }
         Err(err) => {
-            ctx.say(format!("Failed to leave: {err}")).await?;
+            ctx.say(err.to_string()).await?;
         }
     }
 
@@ -676,6 +1426,35 @@ async fn run_presence_task(ctx: serenity::Context, mut rx: SpeakerUpdateReceiver
     apply_presence(&ctx, None).await;
 }
 
+/// Retries queued summaries once their backoff elapses and posts them to the channel
+/// the session was started from.
+async fn run_summary_worker(state: Data, queue: Arc<SummaryQueue>) {
+    let mut ticker = tokio::time::interval(SUMMARY_RETRY_POLL);
+    loop {
+        ticker.tick().await;
+        let Some(ctx) = state.discord_context() else {
+            continue;
+        };
+        for job in queue.claim_due(chrono::Utc::now()) {
+            tracing::info!(
+                session = %job.session_id,
+                attempt = job.attempts + 1,
+                "Retrying queued summary"
+            );
+            let Ok(output) = state.run_summary_job(&job, None).await else {
+                continue;
+            };
+            if let Err(err) = job
+                .channel_id
+                .send_message(&ctx.http, commands::summary_message(&job.label, &output))
+                .await
+            {
+                tracing::warn!(?err, session = %job.session_id, "Failed to post retried summary");
+            }
+        }
+    }
+}
+
 fn current_speaker(rx: &SpeakerUpdateReceiver) -> Option<UserId> {
     {
         let borrow = rx.borrow();
@@ -714,13 +1493,6 @@ fn invite_permissions() -> Permissions {
         | Permissions::USE_VAD
 }
 
-fn transcript_label(summary: &SessionSummary) -> String {
-    match summary.title.as_deref() {
-        Some(title) => format!("{} - {} - Transcription log", summary.date_label(), title),
-        None => format!("{} - Transcription log", summary.date_label()),
-    }
-}
-
 async fn ensure_model_available(config: &BotConfig) -> anyhow::Result<()> {
     if config.whisper_model_path.exists() {
         return Ok(());
@@ -0,0 +1,376 @@
+use std::{borrow::Cow, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+use crate::captions::{CaptionEntry, SessionDocument};
+
+const TAG_PREFIX: &str = "pii:";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedactionMode {
+    /// Captions are stored verbatim and never scanned.
+    Off,
+    /// Matches are replaced before the entry reaches the caption file.
+    Mask,
+    /// Captions are stored verbatim but masked in uploads and summaries.
+    Export,
+    /// Captions are stored verbatim and annotated with `pii:<kind>` tags.
+    Tag,
+}
+
+impl RedactionMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "off" | "none" | "disabled" => Some(Self::Off),
+            "mask" | "write" => Some(Self::Mask),
+            "export" | "exports" => Some(Self::Export),
+            "tag" | "tags" => Some(Self::Tag),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Mask => "mask",
+            Self::Export => "export",
+            Self::Tag => "tag",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Detector {
+    Email,
+    Phone,
+    CreditCard,
+    StreetAddress,
+}
+
+impl Detector {
+    pub const ALL: [Detector; 4] = [
+        Detector::Email,
+        Detector::CreditCard,
+        Detector::Phone,
+        Detector::StreetAddress,
+    ];
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "email" | "emails" => Some(Self::Email),
+            "phone" | "phones" | "phone_number" => Some(Self::Phone),
+            "credit_card" | "card" | "cards" => Some(Self::CreditCard),
+            "address" | "addresses" | "street_address" => Some(Self::StreetAddress),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::CreditCard => "credit_card",
+            Self::StreetAddress => "address",
+        }
+    }
+
+    fn pattern(self) -> &'static str {
+        match self {
+            // Covers both written addresses and Whisper's spoken "name at domain dot com" form.
+            Self::Email => {
+                r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b|\b[a-z0-9._-]+ at [a-z0-9-]+(?: dot [a-z0-9-]+)* dot (?:com|org|net|edu|gov|io|co|uk|de|ca)\b"
+            }
+            Self::Phone => {
+                r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-]?)\d{3}[\s.-]?\d{4}\b"
+            }
+            Self::CreditCard => r"\b\d(?:[ -]?\d){12,18}\b",
+            // Case-sensitive on purpose: the street name and suffix must be capitalised so
+            // ordinary speech such as "2 more in the way" is left alone.
+            Self::StreetAddress => {
+                r"\b\d{1,6}\s+(?:(?:[A-Z][A-Za-z.'-]*|\d+(?:st|nd|rd|th))\s+){1,4}(?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln|Drive|Dr|Court|Ct|Way|Place|Pl|Terrace|Circle|Parkway|Pkwy|Highway|Hwy)\b\.?"
+            }
+        }
+    }
+
+    fn validator(self) -> Option<fn(&str) -> bool> {
+        match self {
+            Self::CreditCard => Some(passes_luhn),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RedactionConfig {
+    pub mode: RedactionMode,
+    pub detectors: Vec<Detector>,
+    pub custom_patterns: Vec<(String, String)>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            mode: RedactionMode::Off,
+            detectors: Detector::ALL.to_vec(),
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+impl RedactionConfig {
+    /// Parses a patterns file where each non-empty line is either `regex` or `label: regex`.
+    pub fn load_patterns_file(path: &Path) -> Result<Vec<(String, String)>> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("reading redaction patterns from {}", path.display()))?;
+        let mut patterns = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (label, pattern) = match line.split_once(": ") {
+                Some((label, pattern))
+                    if !label.is_empty()
+                        && label
+                            .chars()
+                            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-')) =>
+                {
+                    (label.to_ascii_lowercase(), pattern.trim())
+                }
+                _ => ("custom".to_string(), line),
+            };
+            patterns.push((label, pattern.to_string()));
+        }
+        Ok(patterns)
+    }
+}
+
+struct Rule {
+    label: String,
+    pattern: Regex,
+    validator: Option<fn(&str) -> bool>,
+}
+
+pub struct Redaction<'a> {
+    pub text: Cow<'a, str>,
+    pub labels: Vec<String>,
+}
+
+pub struct Redactor {
+    mode: RedactionMode,
+    rules: Vec<Rule>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        let mut rules = Vec::new();
+        if config.mode != RedactionMode::Off {
+            for detector in &config.detectors {
+                rules.push(Rule {
+                    label: detector.label().to_string(),
+                    pattern: Regex::new(detector.pattern())
+                        .expect("built-in redaction pattern must compile"),
+                    validator: detector.validator(),
+                });
+            }
+            for (label, pattern) in &config.custom_patterns {
+                let compiled = Regex::new(pattern)
+                    .with_context(|| format!("compiling redaction pattern '{pattern}'"))?;
+                rules.push(Rule {
+                    label: label.clone(),
+                    pattern: compiled,
+                    validator: None,
+                });
+            }
+            if rules.is_empty() {
+                bail!(
+                    "REDACTION_MODE={} requires at least one detector or custom pattern",
+                    config.mode.as_str()
+                );
+            }
+        }
+        Ok(Self {
+            mode: config.mode,
+            rules,
+        })
+    }
+
+    pub fn mode(&self) -> RedactionMode {
+        self.mode
+    }
+
+    /// Masks every detector match and reports which detectors fired.
+    pub fn scan<'a>(&self, text: &'a str) -> Redaction<'a> {
+        let mut current = Cow::Borrowed(text);
+        let mut labels = Vec::new();
+        for rule in &self.rules {
+            let mut matched = false;
+            let replaced = rule
+                .pattern
+                .replace_all(&current, |caps: &regex::Captures<'_>| {
+                    let found = &caps[0];
+                    if rule.validator.is_some_and(|validate| !validate(found)) {
+                        return found.to_string();
+                    }
+                    matched = true;
+                    format!("[REDACTED:{}]", rule.label.to_ascii_uppercase())
+                });
+            if matched {
+                current = Cow::Owned(replaced.into_owned());
+                if !labels.contains(&rule.label) {
+                    labels.push(rule.label.clone());
+                }
+            }
+        }
+        Redaction {
+            text: current,
+            labels,
+        }
+    }
+
+    /// Applies the write-time stage to a freshly transcribed entry. Returns the
+    /// number of detector kinds that matched.
+    pub fn apply_on_write(&self, entry: &mut CaptionEntry) -> usize {
+        match self.mode {
+            RedactionMode::Off | RedactionMode::Export => 0,
+            RedactionMode::Mask => {
+                let redaction = self.scan(&entry.comment);
+                let count = redaction.labels.len();
+                if let Cow::Owned(masked) = redaction.text {
+                    entry.comment = masked;
+                }
+                count
+            }
+            RedactionMode::Tag => {
+                let labels = self.scan(&entry.comment).labels;
+                let count = labels.len();
+                for label in labels {
+                    let tag = format!("{TAG_PREFIX}{label}");
+                    if !entry.tags.contains(&tag) {
+                        entry.tags.push(tag);
+                    }
+                }
+                count
+            }
+        }
+    }
+
+    /// Returns the text that may leave the caption directory (uploads, summaries).
+    pub fn export_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.mode {
+            RedactionMode::Mask | RedactionMode::Export => self.scan(text).text,
+            RedactionMode::Off | RedactionMode::Tag => Cow::Borrowed(text),
+        }
+    }
+
+    pub fn redact_document_for_export(&self, document: &mut SessionDocument) {
+        if !matches!(self.mode, RedactionMode::Mask | RedactionMode::Export) {
+            return;
+        }
+        for entry in &mut document.transcriptions {
+            if let Cow::Owned(masked) = self.scan(&entry.comment).text {
+                entry.comment = masked;
+            }
+        }
+    }
+}
+
+fn passes_luhn(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|ch| ch.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(idx, digit)| {
+            if !idx.is_multiple_of(2) {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                *digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masks(detector: Detector, text: &str) -> bool {
+        let redactor = Redactor::new(&RedactionConfig {
+            mode: RedactionMode::Mask,
+            detectors: vec![detector],
+            custom_patterns: Vec::new(),
+        })
+        .unwrap();
+        !redactor.scan(text).labels.is_empty()
+    }
+
+    #[test]
+    fn email_detector() {
+        assert!(masks(
+            Detector::Email,
+            "write to jane.doe@example.com today"
+        ));
+        assert!(masks(Detector::Email, "it's jane at example dot com"));
+        assert!(!masks(Detector::Email, "meet me at the station dot"));
+        assert!(!masks(Detector::Email, "we are at home"));
+    }
+
+    #[test]
+    fn phone_detector() {
+        assert!(masks(Detector::Phone, "call (555) 123-4567 later"));
+        assert!(masks(Detector::Phone, "my number is +1 555.123.4567"));
+        assert!(!masks(Detector::Phone, "we scored 12 to 4"));
+        assert!(!masks(Detector::Phone, "ticket 12345"));
+    }
+
+    #[test]
+    fn credit_card_detector() {
+        assert!(masks(
+            Detector::CreditCard,
+            "card 4111 1111 1111 1111 expires"
+        ));
+        assert!(masks(Detector::CreditCard, "amex 378282246310005"));
+        assert!(!masks(
+            Detector::CreditCard,
+            "card 4111 1111 1111 1112 expires"
+        ));
+        assert!(!masks(Detector::CreditCard, "order 123456"));
+    }
+
+    #[test]
+    fn street_address_detector() {
+        assert!(masks(
+            Detector::StreetAddress,
+            "I live at 1600 Pennsylvania Avenue now"
+        ));
+        assert!(masks(Detector::StreetAddress, "drop it at 42 5th Ave."));
+        assert!(masks(Detector::StreetAddress, "meet at 12 Old Mill Rd"));
+        assert!(!masks(
+            Detector::StreetAddress,
+            "there are 2 more in the way"
+        ));
+        assert!(!masks(Detector::StreetAddress, "3 of them took the court"));
+        assert!(!masks(
+            Detector::StreetAddress,
+            "we drove 10 miles down the road"
+        ));
+        assert!(!masks(Detector::StreetAddress, "I have 2 Dr appointments"));
+    }
+
+    #[test]
+    fn luhn_checksum() {
+        assert!(passes_luhn("4111111111111111"));
+        assert!(passes_luhn("4111-1111-1111-1111"));
+        assert!(passes_luhn("378282246310005"));
+        assert!(!passes_luhn("4111111111111112"));
+        assert!(!passes_luhn("79927398713"));
+        assert!(!passes_luhn("41111111111111111111"));
+    }
+}
@@ -0,0 +1,486 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{SecondsFormat, Utc};
+use flate2::{Compression, write::GzEncoder};
+use serenity::model::id::GuildId;
+
+use crate::{
+    captions::{
+        CaptionSink,
+        index::{SUMMARY_SUFFIX, session_id_from_file_name, summary_file_name},
+        storage::StoredObject,
+    },
+    telemetry::{AppMetrics, metrics::RetentionRunSnapshot},
+};
+
+const ARCHIVE_SUFFIX: &str = ".json.gz";
+const MAX_REPORTED_FILES: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetentionAction {
+    Delete,
+    Archive,
+}
+
+impl RetentionAction {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "delete" | "remove" => Some(Self::Delete),
+            "archive" | "gzip" | "compress" => Some(Self::Archive),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Archive => "archive",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetentionLimits {
+    pub max_age: Option<Duration>,
+    pub max_bytes: Option<u64>,
+    /// Archives older than this (since they were written) are deleted.
+    pub archive_max_age: Option<Duration>,
+}
+
+impl RetentionLimits {
+    fn is_unbounded(&self) -> bool {
+        self.max_age.is_none() && self.max_bytes.is_none() && self.archive_max_age.is_none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RetentionPolicy {
+    pub action: RetentionAction,
+    pub interval: Duration,
+    pub defaults: RetentionLimits,
+    pub guild_overrides: HashMap<GuildId, RetentionLimits>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        !self.defaults.is_unbounded()
+            || self
+                .guild_overrides
+                .values()
+                .any(|limits| !limits.is_unbounded())
+    }
+
+    fn limits_for(&self, guild_id: GuildId) -> RetentionLimits {
+        self.guild_overrides
+            .get(&guild_id)
+            .copied()
+            .unwrap_or(self.defaults)
+    }
+
+    /// Parses `guild:max_age_days:max_bytes[:archive_max_age_days]` entries separated by
+    /// commas. Any limit may be left empty, e.g. `123:30:`, `456::500M`, or `789:30::365`.
+    pub fn parse_overrides(raw: &str) -> Result<HashMap<GuildId, RetentionLimits>> {
+        let mut overrides = HashMap::new();
+        for item in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let mut parts = item.split(':');
+            let guild = parts
+                .next()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .filter(|value| *value > 0)
+                .ok_or_else(|| anyhow!("invalid guild id in retention override '{item}'"))?;
+            let max_age =
+                match parts.next().map(str::trim) {
+                    Some("") | None => None,
+                    Some(days) => Some(parse_days(days).with_context(|| {
+                        format!("invalid max age in retention override '{item}'")
+                    })?),
+                };
+            let max_bytes = match parts.next().map(str::trim) {
+                Some("") | None => None,
+                Some(size) => Some(
+                    parse_size(size)
+                        .with_context(|| format!("invalid size in retention override '{item}'"))?,
+                ),
+            };
+            let archive_max_age = match parts.next().map(str::trim) {
+                Some("") | None => None,
+                Some(days) => Some(parse_days(days).with_context(|| {
+                    format!("invalid archive max age in retention override '{item}'")
+                })?),
+            };
+            overrides.insert(
+                GuildId::new(guild),
+                RetentionLimits {
+                    max_age,
+                    max_bytes,
+                    archive_max_age,
+                },
+            );
+        }
+        Ok(overrides)
+    }
+}
+
+pub fn parse_days(raw: &str) -> Result<Duration> {
+    let days: f64 = raw
+        .trim()
+        .trim_end_matches(['d', 'D'])
+        .parse()
+        .context("expected a number of days")?;
+    if !days.is_finite() || days <= 0.0 {
+        return Err(anyhow!("retention age must be positive"));
+    }
+    Ok(Duration::from_secs_f64(days * 86_400.0))
+}
+
+/// Parses byte sizes such as `1048576`, `500M`, or `2GB` (binary multiples).
+pub fn parse_size(raw: &str) -> Result<u64> {
+    let trimmed = raw.trim().to_ascii_uppercase();
+    let trimmed = trimmed.trim_end_matches('B');
+    let (digits, multiplier) = match trimmed.chars().last() {
+        Some('K') => (&trimmed[..trimmed.len() - 1], 1u64 << 10),
+        Some('M') => (&trimmed[..trimmed.len() - 1], 1 << 20),
+        Some('G') => (&trimmed[..trimmed.len() - 1], 1 << 30),
+        Some('T') => (&trimmed[..trimmed.len() - 1], 1 << 40),
+        _ => (trimmed, 1),
+    };
+    let value: u64 = digits.trim().parse().context("expected a byte count")?;
+    Ok(value.saturating_mul(multiplier))
+}
+
+struct StoredSession {
+    /// Current object name; changes to the `.json.gz` name once archived.
+    object: String,
+    file_name: String,
+    size: u64,
+    /// Size of the `.summary.md` stored beside the session, which is deleted with it.
+    summary_size: u64,
+    modified: SystemTime,
+    archived: bool,
+    removed: bool,
+}
+
+pub fn spawn_retention_task(
+    policy: RetentionPolicy,
+    sink: Arc<CaptionSink>,
+    metrics: Arc<AppMetrics>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(policy.interval);
+        loop {
+            ticker.tick().await;
+            let policy = policy.clone();
+            let sink = Arc::clone(&sink);
+            match tokio::task::spawn_blocking(move || run_retention(&policy, &sink)).await {
+                Ok(snapshot) => metrics.record_retention_run(snapshot),
+                Err(err) => tracing::error!("retention task join error: {err}"),
+            }
+        }
+    })
+}
+
+pub fn run_retention(policy: &RetentionPolicy, sink: &CaptionSink) -> RetentionRunSnapshot {
+    let started = Instant::now();
+    let mut snapshot = RetentionRunSnapshot {
+        started_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        action: policy.action.as_str().to_string(),
+        ..RetentionRunSnapshot::default()
+    };
+
+    // In database-primary mode sessions are rows, which can only be deleted, and their
+    // sizes are entry counts rather than bytes, so only the age limit applies.
+    let primary = sink.database_is_primary();
+    let action = if primary {
+        RetentionAction::Delete
+    } else {
+        policy.action
+    };
+    let active = sink.active_session_files();
+    let sessions = match sink
+        .list_sessions()
+        .map(|objects| collect_sessions(objects, &active))
+    {
+        Ok(sessions) => sessions,
+        Err(err) => {
+            tracing::error!(?err, "Retention run could not list caption directory");
+            snapshot.errors += 1;
+            snapshot.duration_ms = started.elapsed().as_millis() as u64;
+            return snapshot;
+        }
+    };
+
+    let now = SystemTime::now();
+    for (guild_id, mut files) in sessions {
+        snapshot.examined += files.len();
+        let limits = policy.limits_for(guild_id);
+        files.sort_by_key(|file| file.modified);
+
+        if let Some(max_age) = limits.max_age {
+            for file in files.iter_mut() {
+                let age = now.duration_since(file.modified).unwrap_or_default();
+                if age > max_age && !file.archived {
+                    apply_action(sink, action, guild_id, file, "age", &mut snapshot);
+                }
+            }
+        }
+
+        // Archiving rewrites the object, so an archive's age counts from when it was made.
+        if let Some(archive_max_age) = limits.archive_max_age {
+            for file in files
+                .iter_mut()
+                .filter(|file| file.archived && !file.removed)
+            {
+                let age = now.duration_since(file.modified).unwrap_or_default();
+                if age > archive_max_age {
+                    apply_action(
+                        sink,
+                        RetentionAction::Delete,
+                        guild_id,
+                        file,
+                        "archive age",
+                        &mut snapshot,
+                    );
+                }
+            }
+        }
+
+        if let Some(max_bytes) = limits.max_bytes.filter(|_| !primary) {
+            enforce_quota(sink, action, guild_id, &mut files, max_bytes, &mut snapshot);
+        }
+    }
+
+    snapshot.duration_ms = started.elapsed().as_millis() as u64;
+    tracing::info!(
+        examined = snapshot.examined,
+        deleted = snapshot.deleted,
+        archived = snapshot.archived,
+        bytes_freed = snapshot.bytes_freed,
+        errors = snapshot.errors,
+        "Retention run finished"
+    );
+    snapshot
+}
+
+fn enforce_quota(
+    sink: &CaptionSink,
+    action: RetentionAction,
+    guild_id: GuildId,
+    files: &mut [StoredSession],
+    max_bytes: u64,
+    snapshot: &mut RetentionRunSnapshot,
+) {
+    let total = |files: &[StoredSession]| -> u64 {
+        files
+            .iter()
+            .filter(|file| !file.removed)
+            .map(|file| file.size + file.summary_size)
+            .sum()
+    };
+
+    // Oldest live sessions are archived (or deleted) first.
+    for idx in 0..files.len() {
+        if total(files) <= max_bytes {
+            return;
+        }
+        if !files[idx].archived && !files[idx].removed {
+            apply_action(sink, action, guild_id, &mut files[idx], "quota", snapshot);
+        }
+    }
+
+    // If archiving alone was not enough, drop the oldest archives.
+    for idx in 0..files.len() {
+        if total(files) <= max_bytes {
+            return;
+        }
+        if !files[idx].removed {
+            apply_action(
+                sink,
+                RetentionAction::Delete,
+                guild_id,
+                &mut files[idx],
+                "quota",
+                snapshot,
+            );
+        }
+    }
+}
+
+fn apply_action(
+    sink: &CaptionSink,
+    action: RetentionAction,
+    guild_id: GuildId,
+    file: &mut StoredSession,
+    reason: &str,
+    snapshot: &mut RetentionRunSnapshot,
+) {
+    let result = match action {
+        RetentionAction::Delete => delete_session(sink, &file.object).map(|_| {
+            file.removed = true;
+            file.size + file.summary_size
+        }),
+        RetentionAction::Archive => {
+            archive_file(sink, &file.object).map(|(archive, archive_size)| {
+                let freed = file.size.saturating_sub(archive_size);
+                file.object = archive;
+                file.size = archive_size;
+                file.modified = SystemTime::now();
+                file.archived = true;
+                freed
+            })
+        }
+    };
+
+    match result {
+        Ok(freed) => {
+            tracing::info!(
+                guild = %guild_id,
+                file = %file.file_name,
+                action = action.as_str(),
+                reason,
+                bytes_freed = freed,
+                "Retention removed caption session"
+            );
+            match action {
+                RetentionAction::Delete => snapshot.deleted += 1,
+                RetentionAction::Archive => snapshot.archived += 1,
+            }
+            snapshot.bytes_freed += freed;
+            if snapshot.affected_files.len() < MAX_REPORTED_FILES {
+                snapshot.affected_files.push(file.file_name.clone());
+            }
+        }
+        Err(err) => {
+            tracing::error!(guild = %guild_id, file = %file.file_name, ?err, "Retention action failed");
+            snapshot.errors += 1;
+        }
+    }
+}
+
+/// Deletes a session together with the summary stored beside it and its database rows.
+fn delete_session(sink: &CaptionSink, name: &str) -> Result<()> {
+    let store = sink.store();
+    if !sink.database_is_primary() {
+        store.delete(name)?;
+    }
+    if let Some((session_id, _)) = session_id_from_file_name(name) {
+        store.delete(&summary_file_name(&session_id))?;
+        sink.delete_database_session(&session_id)?;
+    }
+    Ok(())
+}
+
+fn archive_file(sink: &CaptionSink, name: &str) -> Result<(String, u64)> {
+    let store = sink.store();
+    let Some(bytes) = store.read(name)? else {
+        bail!("{name} disappeared before it could be archived");
+    };
+    let archive = format!("{name}.gz");
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 4), Compression::default());
+    encoder.write_all(&bytes)?;
+    let compressed = encoder.finish()?;
+    store.write(&archive, &compressed)?;
+    store.delete(name)?;
+    Ok((archive, compressed.len() as u64))
+}
+
+fn collect_sessions(
+    objects: Vec<StoredObject>,
+    active: &HashSet<String>,
+) -> HashMap<GuildId, Vec<StoredSession>> {
+    let mut sessions: HashMap<GuildId, Vec<StoredSession>> = HashMap::new();
+    let summary_sizes: HashMap<String, u64> = objects
+        .iter()
+        .filter_map(|object| {
+            let session_id = object.name.strip_suffix(SUMMARY_SUFFIX)?;
+            Some((session_id.to_string(), object.size))
+        })
+        .collect();
+    for object in objects {
+        let file_name = object.name;
+        let archived = file_name.ends_with(ARCHIVE_SUFFIX);
+        if !archived && !file_name.ends_with(".json") {
+            continue;
+        }
+        if active.contains(&file_name) {
+            continue;
+        }
+        let Some(guild_id) = file_name
+            .split('_')
+            .next()
+            .and_then(|raw| raw.parse::<u64>().ok())
+            .filter(|raw| *raw > 0)
+            .map(GuildId::new)
+        else {
+            continue;
+        };
+        let summary_size = session_id_from_file_name(&file_name)
+            .and_then(|(session_id, _)| summary_sizes.get(&session_id).copied())
+            .unwrap_or(0);
+        sessions.entry(guild_id).or_default().push(StoredSession {
+            object: file_name.clone(),
+            file_name,
+            size: object.size,
+            summary_size,
+            modified: object.modified,
+            archived,
+            removed: false,
+        });
+    }
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(name: &str, size: u64) -> StoredObject {
+        StoredObject {
+            name: name.to_string(),
+            size,
+            modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn counts_summaries_toward_their_session() {
+        let sessions = collect_sessions(
+            vec![
+                object("1_2_20260105_090000.json", 100),
+                object("1_2_20260105_090000.summary.md", 40),
+                object("1_2_20260104_090000.json.gz", 30),
+                object("1_2_20260103_090000.summary.md", 7),
+            ],
+            &HashSet::new(),
+        );
+        let files = &sessions[&GuildId::new(1)];
+        assert_eq!(files.len(), 2);
+        let live = files.iter().find(|file| !file.archived).unwrap();
+        assert_eq!((live.size, live.summary_size), (100, 40));
+        let archive = files.iter().find(|file| file.archived).unwrap();
+        assert_eq!((archive.size, archive.summary_size), (30, 0));
+    }
+
+    #[test]
+    fn parses_archive_age_overrides() {
+        let overrides = RetentionPolicy::parse_overrides("123:30:,456::500M:365").unwrap();
+        let first = overrides[&GuildId::new(123)];
+        assert_eq!(first.max_age, Some(Duration::from_secs(30 * 86_400)));
+        assert_eq!(first.archive_max_age, None);
+        let second = overrides[&GuildId::new(456)];
+        assert_eq!(second.max_bytes, Some(500 << 20));
+        assert_eq!(
+            second.archive_max_age,
+            Some(Duration::from_secs(365 * 86_400))
+        );
+    }
+}
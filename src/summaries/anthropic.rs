@@ -0,0 +1,166 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use super::{Summarizer, SummaryConfig, SummaryPrompt, read_json_response};
+
+const API_VERSION: &str = "2023-06-01";
+
+/// Anthropic Messages API (`POST {base}/messages`).
+pub struct AnthropicSummarizer {
+    client: Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    max_tokens: u32,
+}
+
+impl AnthropicSummarizer {
+    pub fn new(config: &SummaryConfig) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: format!("{}/messages", config.base_url),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            max_tokens: config.max_tokens,
+        }
+    }
+}
+
+#[async_trait]
+impl Summarizer for AnthropicSummarizer {
+    fn provider(&self) -> &'static str {
+        "Anthropic"
+    }
+
+    async fn complete(&self, prompt: &SummaryPrompt) -> Result<String> {
+        let content: Vec<Value> = prompt
+            .user
+            .iter()
+            .map(|text| json!({ "type": "text", "text": text }))
+            .collect();
+        let payload = json!({
+            "model": self.model,
+            "max_tokens": self.max_tokens,
+            "system": prompt.system,
+            "messages": [{ "role": "user", "content": content }],
+        });
+
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header("anthropic-version", API_VERSION)
+            .json(&payload);
+        if let Some(key) = self.api_key.as_deref() {
+            request = request.header("x-api-key", key);
+        }
+        let response = request
+            .send()
+            .await
+            .context("requesting transcript summary from Anthropic")?;
+        let body = read_json_response(self.provider(), response).await?;
+
+        extract_text(&body)
+            .ok_or_else(|| anyhow!("Anthropic summary response did not include text: {}", body))
+    }
+}
+
+/// Joins the `text` blocks of a Messages response, skipping thinking and tool blocks.
+fn extract_text(body: &Value) -> Option<String> {
+    let text = body
+        .get("content")
+        .and_then(Value::as_array)
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+                .filter_map(|block| block.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("")
+        })
+        .unwrap_or_default();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        summaries::{SummaryProvider, test_config},
+        utils::test_http,
+    };
+
+    #[test]
+    fn joins_text_blocks() {
+        let body = json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                { "type": "thinking", "thinking": "Planning the notes" },
+                { "type": "text", "text": "## Overview\n" },
+                { "type": "text", "text": "Shipped the release." },
+            ],
+            "stop_reason": "end_turn",
+        });
+        assert_eq!(
+            extract_text(&body).as_deref(),
+            Some("## Overview\nShipped the release.")
+        );
+    }
+
+    #[test]
+    fn rejects_responses_without_text() {
+        let tool_only = json!({
+            "content": [{ "type": "tool_use", "id": "toolu_1", "name": "notes", "input": {} }]
+        });
+        assert_eq!(extract_text(&tool_only), None);
+        assert_eq!(extract_text(&json!({ "content": [] })), None);
+        assert_eq!(
+            extract_text(&json!({ "type": "error", "error": { "message": "overloaded" } })),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_to_messages_with_api_key_header() {
+        let reply = json!({ "content": [{ "type": "text", "text": "Notes" }] });
+        let (base_url, mut requests) = test_http::serve(vec![(200, reply.to_string())]).await;
+        let summarizer =
+            AnthropicSummarizer::new(&test_config(SummaryProvider::Anthropic, &base_url));
+        let prompt = SummaryPrompt {
+            system: "Summarize.".to_string(),
+            user: vec!["Ana: shipped it".to_string()],
+        };
+        assert_eq!(summarizer.complete(&prompt).await.unwrap(), "Notes");
+
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/messages");
+        assert_eq!(request.header("x-api-key"), Some("test-key"));
+        assert_eq!(request.header("anthropic-version"), Some(API_VERSION));
+        assert_eq!(request.header("authorization"), None);
+        let body = request.json();
+        assert_eq!(body["max_tokens"], 321);
+        assert_eq!(body["system"], "Summarize.");
+    }
+
+    #[tokio::test]
+    async fn maps_http_errors() {
+        let (base_url, _requests) = test_http::serve(vec![(
+            401,
+            r#"{"type":"error","error":{"message":"invalid x-api-key"}}"#.to_string(),
+        )])
+        .await;
+        let summarizer =
+            AnthropicSummarizer::new(&test_config(SummaryProvider::Anthropic, &base_url));
+        let prompt = SummaryPrompt {
+            system: "Summarize.".to_string(),
+            user: vec!["Ana: shipped it".to_string()],
+        };
+        let err = summarizer.complete(&prompt).await.unwrap_err().to_string();
+        assert!(err.contains("status 401"), "{err}");
+        assert!(err.contains("invalid x-api-key"), "{err}");
+    }
+}
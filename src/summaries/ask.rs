@@ -0,0 +1,93 @@
+use anyhow::{Result, bail};
+
+use crate::captions::index::RetrievedLine;
+
+use super::{SummaryPrompt, TranscriptSummarizer};
+
+const ASK_PROMPT: &str = "You answer questions about Discord call transcripts using only the numbered excerpts provided. Cite the excerpts that support each statement by number in square brackets, like [3] or [2][5]. If the excerpts do not answer the question, say so plainly instead of guessing. Keep the answer under 150 words.";
+
+/// A model answer and the excerpts it cited.
+pub struct Answer {
+    pub text: String,
+    /// Indices into the lines passed to [`TranscriptSummarizer::answer`], in order of
+    /// first citation.
+    pub citations: Vec<usize>,
+}
+
+impl TranscriptSummarizer {
+    /// Answers `question` from retrieved caption lines, numbering them `[1]`, `[2]`, …
+    /// for citation. Lines past the request budget are left out; export redaction is
+    /// applied before anything is sent.
+    pub async fn answer(&self, question: &str, lines: &[RetrievedLine]) -> Result<Answer> {
+        if lines.is_empty() {
+            bail!("no transcript excerpts to answer from");
+        }
+        let mut excerpts = String::new();
+        let mut excerpt_chars = 0;
+        let mut current_session = None;
+        let mut offered = 0;
+        for (index, line) in lines.iter().enumerate() {
+            let mut block = String::new();
+            if current_session != Some(line.session_id.as_str()) {
+                current_session = Some(line.session_id.as_str());
+                let title = line.session_title.as_deref().unwrap_or(&line.session_id);
+                block.push_str(&format!("\nSession '{title}':\n"));
+            }
+            block.push_str(&format!(
+                "[{}] [{}] {}: {}\n",
+                index + 1,
+                line.timestamp,
+                line.speaker,
+                self.redactor.export_text(line.comment.trim())
+            ));
+            let block_chars = block.chars().count();
+            if excerpt_chars + block_chars > self.chunk_chars && offered > 0 {
+                break;
+            }
+            excerpts.push_str(&block);
+            excerpt_chars += block_chars;
+            offered += 1;
+        }
+        if offered < lines.len() {
+            tracing::debug!(
+                offered,
+                retrieved = lines.len(),
+                "Question excerpts exceed one request; dropping the rest"
+            );
+        }
+
+        let prompt = SummaryPrompt {
+            system: ASK_PROMPT.to_string(),
+            user: vec![
+                format!("Question: {}", question.trim()),
+                format!("Excerpts:{excerpts}"),
+            ],
+        };
+        let text = self.provider.complete(&prompt).await?;
+        let citations = cited_numbers(&text)
+            .into_iter()
+            .filter(|number| (1..=offered).contains(number))
+            .map(|number| number - 1)
+            .collect();
+        Ok(Answer { text, citations })
+    }
+}
+
+/// Distinct `[n]` markers in order of appearance.
+fn cited_numbers(text: &str) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    for part in text.split('[').skip(1) {
+        let Some((inside, _)) = part.split_once(']') else {
+            continue;
+        };
+        for number in inside
+            .split(',')
+            .filter_map(|raw| raw.trim().parse::<usize>().ok())
+        {
+            if !numbers.contains(&number) {
+                numbers.push(number);
+            }
+        }
+    }
+    numbers
+}
@@ -0,0 +1,138 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use super::{Summarizer, SummaryConfig, SummaryPrompt, read_json_response};
+
+/// OpenAI-compatible Chat Completions (`POST {base}/chat/completions`). Point the base
+/// URL at Ollama (`http://localhost:11434/v1`), a llama.cpp server, or vLLM to keep
+/// transcripts on your own hardware; the API key is optional for those.
+pub struct ChatCompletionsSummarizer {
+    client: Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    max_tokens: u32,
+}
+
+impl ChatCompletionsSummarizer {
+    pub fn new(config: &SummaryConfig) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: format!("{}/chat/completions", config.base_url),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            max_tokens: config.max_tokens,
+        }
+    }
+}
+
+#[async_trait]
+impl Summarizer for ChatCompletionsSummarizer {
+    fn provider(&self) -> &'static str {
+        "Chat Completions"
+    }
+
+    async fn complete(&self, prompt: &SummaryPrompt) -> Result<String> {
+        let payload = json!({
+            "model": self.model,
+            "max_tokens": self.max_tokens,
+            "messages": [
+                { "role": "system", "content": prompt.system },
+                { "role": "user", "content": prompt.user.join("\n\n") },
+            ],
+        });
+
+        let mut request = self.client.post(&self.endpoint).json(&payload);
+        if let Some(key) = self.api_key.as_deref() {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("requesting transcript summary from {}", self.endpoint))?;
+        let body = read_json_response(self.provider(), response).await?;
+
+        extract_message_text(&body)
+            .ok_or_else(|| anyhow!("Chat Completions response did not include text: {}", body))
+    }
+}
+
+fn extract_message_text(body: &Value) -> Option<String> {
+    body.pointer("/choices/0/message/content")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        summaries::{SummaryProvider, test_config},
+        utils::test_http,
+    };
+
+    #[test]
+    fn reads_first_choice_content() {
+        let body = json!({
+            "choices": [
+                { "index": 0, "message": { "role": "assistant", "content": "  Notes\n" } },
+                { "index": 1, "message": { "role": "assistant", "content": "Other" } },
+            ]
+        });
+        assert_eq!(extract_message_text(&body).as_deref(), Some("Notes"));
+    }
+
+    #[test]
+    fn rejects_empty_or_missing_content() {
+        let empty = json!({ "choices": [{ "message": { "content": "  " } }] });
+        assert_eq!(extract_message_text(&empty), None);
+        let refused = json!({ "choices": [{ "message": { "content": null, "refusal": "no" } }] });
+        assert_eq!(extract_message_text(&refused), None);
+        assert_eq!(extract_message_text(&json!({ "choices": [] })), None);
+    }
+
+    #[tokio::test]
+    async fn posts_to_chat_completions_with_bearer_auth() {
+        let reply =
+            json!({ "choices": [{ "message": { "role": "assistant", "content": "Notes" } }] });
+        let (base_url, mut requests) = test_http::serve(vec![(200, reply.to_string())]).await;
+        let summarizer = ChatCompletionsSummarizer::new(&test_config(
+            SummaryProvider::ChatCompletions,
+            &base_url,
+        ));
+        let prompt = SummaryPrompt {
+            system: "Summarize.".to_string(),
+            user: vec!["Ana: shipped it".to_string()],
+        };
+        assert_eq!(summarizer.complete(&prompt).await.unwrap(), "Notes");
+
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/chat/completions");
+        assert_eq!(request.header("authorization"), Some("Bearer test-key"));
+        let body = request.json();
+        assert_eq!(body["max_tokens"], 321);
+        assert_eq!(body["messages"][0]["role"], "system");
+    }
+
+    #[tokio::test]
+    async fn maps_http_errors() {
+        let (base_url, _requests) =
+            test_http::serve(vec![(500, r#"{"error":"model not loaded"}"#.to_string())]).await;
+        let summarizer = ChatCompletionsSummarizer::new(&test_config(
+            SummaryProvider::ChatCompletions,
+            &base_url,
+        ));
+        let prompt = SummaryPrompt {
+            system: "Summarize.".to_string(),
+            user: vec!["Ana: shipped it".to_string()],
+        };
+        let err = summarizer.complete(&prompt).await.unwrap_err().to_string();
+        assert!(err.contains("status 500"), "{err}");
+        assert!(err.contains("model not loaded"), "{err}");
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result, bail};
+
+use super::{SummaryPrompt, TranscriptSummarizer, chunk_lines};
+
+const DIGEST_PROMPT: &str = "You write a digest of several Discord calls for people who did not attend. From the per-call notes provided, write terse markdown: a short overview, then bullets for the main topics, decisions, and open action items with their owners. Name the call a point came from when it helps. Do not add an introduction or conclusion.";
+const DIGEST_MERGE_PROMPT: &str = "You merge partial digests of Discord calls from the same period into one digest with the same shape: a short overview, then bullets for topics, decisions, and open action items with their owners. Keep every decision and action item; drop repetition.";
+/// Smallest share of the request budget given to one session, so a busy period still
+/// says something about each call.
+const MIN_SESSION_CHARS: usize = 600;
+
+/// One finished session as input to [`TranscriptSummarizer::digest`].
+pub struct DigestSession {
+    pub label: String,
+    /// Stored summary of the session, if one was produced.
+    pub summary: Option<String>,
+    /// `speaker: text` caption lines, used when there is no summary.
+    pub lines: Vec<String>,
+}
+
+impl TranscriptSummarizer {
+    /// Combines several sessions into one digest. Each session contributes its stored
+    /// summary, or the start of its transcript (with export redaction) when it has none;
+    /// periods that do not fit one request are digested in groups and then merged.
+    pub async fn digest(&self, period: &str, sessions: &[DigestSession]) -> Result<String> {
+        if sessions.is_empty() {
+            bail!("no sessions to digest");
+        }
+        let budget = (self.chunk_chars / sessions.len()).max(MIN_SESSION_CHARS);
+        let blocks: Vec<String> = sessions
+            .iter()
+            .map(|session| self.digest_block(session, budget))
+            .collect();
+        let groups = chunk_lines(&blocks, self.chunk_chars);
+        if groups.len() <= 1 {
+            return self
+                .complete_digest(DIGEST_PROMPT, period, blocks.concat())
+                .await;
+        }
+
+        let total = groups.len();
+        let mut partials = String::new();
+        for (index, group) in groups.into_iter().enumerate() {
+            let partial = self
+                .complete_digest(DIGEST_PROMPT, period, group)
+                .await
+                .with_context(|| format!("digesting session group {} of {total}", index + 1))?;
+            partials.push_str(&format!("Part {}:\n{}\n\n", index + 1, partial.trim()));
+        }
+        self.complete_digest(DIGEST_MERGE_PROMPT, period, partials)
+            .await
+    }
+
+    fn digest_block(&self, session: &DigestSession, budget: usize) -> String {
+        let mut block = match session.summary.as_deref().map(str::trim) {
+            Some(summary) if !summary.is_empty() => {
+                format!("Call '{}' notes:\n{summary}\n", session.label)
+            }
+            _ => {
+                let mut block = format!("Call '{}' transcript excerpt:\n", session.label);
+                for line in &session.lines {
+                    block.push_str(&self.redactor.export_text(line.trim()));
+                    block.push('\n');
+                }
+                block
+            }
+        };
+        if block.chars().count() > budget {
+            block = block.chars().take(budget.saturating_sub(2)).collect();
+            block.push_str("…\n");
+        }
+        block.push('\n');
+        block
+    }
+
+    async fn complete_digest(&self, system: &str, period: &str, body: String) -> Result<String> {
+        self.provider
+            .complete(&SummaryPrompt {
+                system: system.to_string(),
+                user: vec![format!("Calls from {period}."), body],
+            })
+            .await
+            .context("writing session digest")
+    }
+}
@@ -1,3 +1,680 @@
+pub mod anthropic;
+pub mod ask;
+pub mod chat;
+pub mod digest;
 pub mod openai;
+pub mod queue;
+pub mod rolling;
+pub mod structured;
+pub mod templates;
 
-pub use openai::OpenAiSummarizer;
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use chrono::DateTime;
+use futures_util::future::try_join_all;
+use serde::Deserialize;
+use serde_json::Value;
+use serenity::model::id::{GuildId, UserId};
+use tokio::sync::mpsc;
+
+use crate::{captions::SpeakerInfo, redaction::Redactor};
+
+pub use anthropic::AnthropicSummarizer;
+pub use chat::ChatCompletionsSummarizer;
+pub use digest::DigestSession;
+pub use openai::ResponsesSummarizer;
+pub use queue::{JobStatus, SummaryJob, SummaryQueue, SummaryQueueConfig};
+pub use rolling::{RollingSummaryConfig, RollingSummaryFeed};
+pub use structured::StructuredSummary;
+pub use templates::{SummaryTemplates, TemplateVars};
+
+const MAP_PROMPT: &str = "You summarize one part of a longer Discord call transcript. List the topics discussed, decisions made, and action items with their owners as terse markdown bullets. Do not add an introduction or conclusion.";
+const ROLLING_PROMPT: &str = "You write running notes for a Discord call that is still in progress. Summarize only the new transcript excerpt as terse markdown bullets: topics, decisions, and action items with their owners. Earlier notes are context for resolving references; do not repeat them. Keep the answer under 120 words.";
+const MERGE_PROMPT: &str = "You merge partial notes from consecutive parts of a Discord call into a single set of terse markdown bullets. Keep every decision and action item; drop repetition.";
+
+/// Chunk summaries requested concurrently during the map pass; progress is reported
+/// after each batch.
+const MAP_CONCURRENCY: usize = 3;
+/// Rough characters-per-token ratio for English chat text; avoids shipping a tokenizer
+/// for every provider while staying well inside context windows.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// A single summary request: a system instruction plus ordered user text blocks.
+pub struct SummaryPrompt {
+    pub system: String,
+    pub user: Vec<String>,
+}
+
+/// An LLM API that turns a prompt into text. Implementations only handle the wire
+/// format; transcript preparation lives in [`TranscriptSummarizer`].
+#[async_trait]
+pub trait Summarizer: Send + Sync {
+    /// Provider name for logs and error messages.
+    fn provider(&self) -> &'static str;
+
+    async fn complete(&self, prompt: &SummaryPrompt) -> Result<String>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SummaryProvider {
+    /// OpenAI Responses API (`POST {base}/responses`).
+    OpenAiResponses,
+    /// OpenAI-compatible Chat Completions (`POST {base}/chat/completions`), which also
+    /// covers Ollama, llama.cpp, and vLLM servers.
+    ChatCompletions,
+    /// Anthropic Messages API (`POST {base}/messages`).
+    Anthropic,
+}
+
+impl SummaryProvider {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "openai" | "responses" | "openai-responses" => Some(Self::OpenAiResponses),
+            "chat" | "chat-completions" | "openai-chat" | "openai-compatible" | "ollama"
+            | "llama.cpp" | "vllm" => Some(Self::ChatCompletions),
+            "anthropic" | "messages" => Some(Self::Anthropic),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OpenAiResponses => "openai-responses",
+            Self::ChatCompletions => "chat-completions",
+            Self::Anthropic => "anthropic",
+        }
+    }
+
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            Self::OpenAiResponses | Self::ChatCompletions => "https://api.openai.com/v1",
+            Self::Anthropic => "https://api.anthropic.com/v1",
+        }
+    }
+}
+
+/// Shape of the final summary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryFormat {
+    /// Free-form markdown notes.
+    Markdown,
+    /// Schema-validated JSON with decisions, owned action items, open questions, and
+    /// topics; stored in the session document and posted as an embed.
+    Structured,
+}
+
+impl SummaryFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "text" | "md" => Some(Self::Markdown),
+            "structured" | "json" => Some(Self::Structured),
+            _ => None,
+        }
+    }
+}
+
+/// A finished summary in the configured [`SummaryFormat`].
+#[derive(Clone, Debug)]
+pub enum SummaryOutput {
+    Markdown(String),
+    Structured(StructuredSummary),
+}
+
+impl SummaryOutput {
+    /// Markdown text for plain-text destinations (chat messages, webhooks).
+    pub fn to_markdown(&self) -> String {
+        match self {
+            Self::Markdown(text) => text.clone(),
+            Self::Structured(summary) => summary.to_markdown(),
+        }
+    }
+
+    pub fn structured(&self) -> Option<&StructuredSummary> {
+        match self {
+            Self::Markdown(_) => None,
+            Self::Structured(summary) => Some(summary),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SummaryConfig {
+    pub provider: SummaryProvider,
+    /// API root without a trailing slash, e.g. `http://localhost:11434/v1`.
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    pub max_tokens: u32,
+    /// Estimated input tokens per request; longer transcripts are summarized in chunks.
+    pub chunk_tokens: usize,
+    pub format: SummaryFormat,
+    pub templates: Arc<SummaryTemplates>,
+    /// Where summaries owed for finished sessions are persisted until they succeed.
+    pub queue: SummaryQueueConfig,
+    /// Default spacing of running notes posted during a session; `None` leaves them to
+    /// `/join summary_every`.
+    pub rolling_interval: Option<Duration>,
+}
+
+impl SummaryConfig {
+    pub fn build(&self, redactor: Arc<Redactor>) -> TranscriptSummarizer {
+        let provider: Arc<dyn Summarizer> = match self.provider {
+            SummaryProvider::OpenAiResponses => Arc::new(ResponsesSummarizer::new(self)),
+            SummaryProvider::ChatCompletions => Arc::new(ChatCompletionsSummarizer::new(self)),
+            SummaryProvider::Anthropic => Arc::new(AnthropicSummarizer::new(self)),
+        };
+        TranscriptSummarizer {
+            provider,
+            redactor,
+            chunk_chars: self.chunk_tokens.saturating_mul(CHARS_PER_TOKEN),
+            format: self.format,
+            templates: Arc::clone(&self.templates),
+        }
+    }
+}
+
+/// Progress of a chunked summary, reported while long sessions are processed.
+#[derive(Clone, Copy, Debug)]
+pub enum SummaryProgress {
+    /// `done` of `total` transcript chunks have been summarized.
+    Chunk { done: usize, total: usize },
+    /// All chunks are summarized and the final notes are being written.
+    Reducing,
+}
+
+/// Turns stored session documents into summaries using the configured provider.
+///
+/// Transcripts that fit in one request are summarized directly. Longer ones are split
+/// on entry boundaries, each chunk is summarized (map), and the partial notes are
+/// merged into the final summary (reduce).
+#[derive(Clone)]
+pub struct TranscriptSummarizer {
+    provider: Arc<dyn Summarizer>,
+    redactor: Arc<Redactor>,
+    chunk_chars: usize,
+    format: SummaryFormat,
+    templates: Arc<SummaryTemplates>,
+}
+
+/// What to summarize a document as.
+pub struct SummaryRequest<'a> {
+    /// Shown to the model and used for `{title}` when the session has no title.
+    pub label: &'a str,
+    pub guild_id: Option<GuildId>,
+    /// Template chosen for this run; otherwise the one recorded on the session, the
+    /// guild default, or `default`.
+    pub template: Option<&'a str>,
+}
+
+/// System prompt and output shape for the final summary request.
+struct FinalPrompt<'a> {
+    system: String,
+    format: SummaryFormat,
+    participants: &'a [SpeakerInfo],
+}
+
+impl TranscriptSummarizer {
+    pub fn provider(&self) -> &'static str {
+        self.provider.provider()
+    }
+
+    pub fn templates(&self) -> &SummaryTemplates {
+        &self.templates
+    }
+
+    /// Summarizes a plaintext session document (already decrypted by `CaptionSink`),
+    /// sending a [`SummaryProgress`] update after each batch of chunks when the
+    /// transcript needs more than one request. The sender is dropped when it finishes.
+    pub async fn summarize_with_progress(
+        &self,
+        document_json: &[u8],
+        request: &SummaryRequest<'_>,
+        progress: Option<mpsc::UnboundedSender<SummaryProgress>>,
+    ) -> Result<SummaryOutput> {
+        let transcript = flatten_transcript(document_json, &self.redactor)
+            .context("preparing transcript for summary upload")?;
+        let label = if request.label.trim().is_empty() {
+            "Discord session".to_string()
+        } else {
+            request.label.trim().to_string()
+        };
+        let final_prompt = self.final_prompt(request, &transcript, &label)?;
+
+        let chunks = chunk_lines(&transcript.lines, self.chunk_chars);
+        if chunks.len() <= 1 {
+            return self
+                .finish(
+                    &final_prompt,
+                    format!("Summarize the session titled '{label}'."),
+                    transcript.render(&transcript.lines.concat()),
+                )
+                .await;
+        }
+
+        let total = chunks.len();
+        tracing::info!(
+            provider = self.provider(),
+            chunks = total,
+            "Transcript exceeds one summary request; summarizing in chunks"
+        );
+        let report = |update: SummaryProgress| {
+            if let Some(progress) = progress.as_ref() {
+                let _ = progress.send(update);
+            }
+        };
+        report(SummaryProgress::Chunk { done: 0, total });
+
+        let mut partials = Vec::with_capacity(total);
+        for batch in chunks.chunks(MAP_CONCURRENCY) {
+            let offset = partials.len();
+            let requests = batch.iter().enumerate().map(|(index, chunk)| {
+                self.summarize_chunk(&label, offset + index + 1, total, transcript.render(chunk))
+            });
+            partials.extend(try_join_all(requests).await?);
+            report(SummaryProgress::Chunk {
+                done: partials.len(),
+                total,
+            });
+        }
+
+        report(SummaryProgress::Reducing);
+        self.reduce(&label, partials, &final_prompt).await
+    }
+
+    /// Resolves the template and renders its system prompt for this transcript. A
+    /// template recorded on the session that no longer exists falls back to the
+    /// guild default instead of failing.
+    fn final_prompt<'t>(
+        &self,
+        request: &SummaryRequest<'_>,
+        transcript: &'t FlattenedTranscript,
+        label: &str,
+    ) -> Result<FinalPrompt<'t>> {
+        let (name, template) = match (request.template, transcript.template.as_deref()) {
+            (Some(requested), _) => self.templates.resolve(request.guild_id, Some(requested))?,
+            (None, Some(stored)) => self
+                .templates
+                .resolve(request.guild_id, Some(stored))
+                .or_else(|err| {
+                    tracing::warn!(template = stored, %err, "Using the default summary template");
+                    self.templates.resolve(request.guild_id, None)
+                })?,
+            (None, None) => self.templates.resolve(request.guild_id, None)?,
+        };
+        let participants = transcript
+            .participants
+            .iter()
+            .map(|speaker| speaker.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let system = template.render(&TemplateVars {
+            title: transcript.title.as_deref().unwrap_or(label),
+            participants: &participants,
+            duration: transcript.duration.as_deref().unwrap_or("unknown"),
+            date: &transcript.date,
+        });
+        tracing::debug!(template = name, "Rendering summary template");
+        Ok(FinalPrompt {
+            system,
+            format: template.format.unwrap_or(self.format),
+            participants: &transcript.participants,
+        })
+    }
+
+    /// Final request of a summary. Markdown mode sends the template prompt as is;
+    /// structured mode prepends the JSON schema instructions and retries once with
+    /// the validation error when the reply does not parse.
+    async fn finish(
+        &self,
+        final_prompt: &FinalPrompt<'_>,
+        instruction: String,
+        body: String,
+    ) -> Result<SummaryOutput> {
+        let participants = final_prompt.participants;
+        if final_prompt.format == SummaryFormat::Markdown {
+            let prompt = SummaryPrompt {
+                system: final_prompt.system.clone(),
+                user: vec![instruction, body],
+            };
+            return self
+                .provider
+                .complete(&prompt)
+                .await
+                .map(SummaryOutput::Markdown);
+        }
+
+        let mut prompt = SummaryPrompt {
+            system: format!("{}\n\n{}", structured::SYSTEM_PROMPT, final_prompt.system),
+            user: vec![
+                instruction,
+                structured::participant_block(participants),
+                body,
+            ],
+        };
+        let reply = self.provider.complete(&prompt).await?;
+        let err = match StructuredSummary::parse(&reply, participants) {
+            Ok(summary) => return Ok(SummaryOutput::Structured(summary)),
+            Err(err) => err,
+        };
+        tracing::warn!(
+            provider = self.provider(),
+            error = %format!("{err:#}"),
+            "Structured summary was invalid; retrying once"
+        );
+        prompt.user.push(format!(
+            "Your previous reply was invalid ({err:#}). Reply again with only the JSON object."
+        ));
+        let reply = self.provider.complete(&prompt).await?;
+        StructuredSummary::parse(&reply, participants)
+            .map(SummaryOutput::Structured)
+            .context("structured summary did not match the schema")
+    }
+
+    /// Notes for a stretch of a session that is still recording. `document_json` holds
+    /// only the entries since the previous update; `context` carries recent notes so
+    /// the model can follow the thread without the whole transcript.
+    pub async fn summarize_increment(
+        &self,
+        document_json: &[u8],
+        label: &str,
+        context: Option<&str>,
+    ) -> Result<String> {
+        let transcript = flatten_transcript(document_json, &self.redactor)
+            .context("preparing transcript excerpt for rolling summary")?;
+        let chunks = chunk_lines(&transcript.lines, self.chunk_chars);
+        let body = if chunks.len() <= 1 {
+            transcript.render(&transcript.lines.concat())
+        } else {
+            let total = chunks.len();
+            let mut notes = String::from("Notes on consecutive parts of the new excerpt:\n");
+            for (index, chunk) in chunks.iter().enumerate() {
+                let partial = self
+                    .summarize_chunk(label, index + 1, total, transcript.render(chunk))
+                    .await?;
+                notes.push_str(&format!("Part {}:\n{}\n\n", index + 1, partial.trim()));
+            }
+            notes
+        };
+
+        let mut user = vec![format!("Session '{label}', new excerpt.")];
+        if let Some(context) = context.map(str::trim).filter(|text| !text.is_empty()) {
+            user.push(format!("Earlier notes:\n{context}"));
+        }
+        user.push(body);
+        self.provider
+            .complete(&SummaryPrompt {
+                system: ROLLING_PROMPT.to_string(),
+                user,
+            })
+            .await
+            .context("writing rolling summary")
+    }
+
+    async fn summarize_chunk(
+        &self,
+        label: &str,
+        part: usize,
+        total: usize,
+        body: String,
+    ) -> Result<String> {
+        let prompt = SummaryPrompt {
+            system: MAP_PROMPT.to_string(),
+            user: vec![format!("Session '{label}', part {part} of {total}."), body],
+        };
+        self.provider
+            .complete(&prompt)
+            .await
+            .with_context(|| format!("summarizing transcript part {part}"))
+    }
+
+    /// Merges partial notes into the final summary, first collapsing groups of partials
+    /// when all of them together would not fit in a single request.
+    async fn reduce(
+        &self,
+        label: &str,
+        mut partials: Vec<String>,
+        final_prompt: &FinalPrompt<'_>,
+    ) -> Result<SummaryOutput> {
+        loop {
+            let numbered: Vec<String> = partials
+                .iter()
+                .enumerate()
+                .map(|(index, notes)| format!("Part {}:\n{}\n\n", index + 1, notes.trim()))
+                .collect();
+            let groups = chunk_lines(&numbered, self.chunk_chars);
+            if groups.len() <= 1 || groups.len() == partials.len() {
+                return self
+                    .finish(
+                        final_prompt,
+                        format!(
+                            "These are notes from consecutive parts of the session titled '{label}'. Combine them into one summary."
+                        ),
+                        numbered.concat(),
+                    )
+                    .await;
+            }
+
+            let mut merged = Vec::with_capacity(groups.len());
+            for group in groups {
+                let prompt = SummaryPrompt {
+                    system: MERGE_PROMPT.to_string(),
+                    user: vec![format!("Session '{label}'."), group],
+                };
+                merged.push(
+                    self.provider
+                        .complete(&prompt)
+                        .await
+                        .context("merging partial transcript summaries")?,
+                );
+            }
+            partials = merged;
+        }
+    }
+}
+
+/// Reads a provider response body, turning non-2xx statuses into errors that include
+/// the body for troubleshooting.
+pub(crate) async fn read_json_response(
+    provider: &str,
+    response: reqwest::Response,
+) -> Result<Value> {
+    let status = response.status();
+    let bytes = response
+        .bytes()
+        .await
+        .with_context(|| format!("reading {provider} summary response body"))?;
+    if !status.is_success() {
+        let body = String::from_utf8_lossy(&bytes);
+        bail!("{provider} summary request failed: status {status}, body: {body}");
+    }
+    serde_json::from_slice(&bytes).with_context(|| format!("parsing {provider} summary response"))
+}
+
+/// A transcript flattened to text: metadata header lines plus one line per entry.
+struct FlattenedTranscript {
+    header: String,
+    lines: Vec<String>,
+    /// Distinct speakers in order of first appearance, for assigning action items.
+    participants: Vec<SpeakerInfo>,
+    title: Option<String>,
+    duration: Option<String>,
+    /// Session start date (`YYYY-MM-DD`), or empty when unknown.
+    date: String,
+    /// Template recorded on the session at `/join`.
+    template: Option<String>,
+}
+
+impl FlattenedTranscript {
+    /// Prefixes `body` with the session metadata so every chunk carries context.
+    fn render(&self, body: &str) -> String {
+        format!("{}Transcript:\n{}", self.header, body)
+    }
+}
+
+fn flatten_transcript(bytes: &[u8], redactor: &Redactor) -> Result<FlattenedTranscript> {
+    let value: Value = serde_json::from_slice(bytes).context("parsing caption JSON")?;
+
+    let mut header = String::new();
+
+    if let Some(metadata) = value.get("metadata").and_then(Value::as_object) {
+        if let Some(title) = metadata.get("title").and_then(Value::as_str) {
+            let trimmed = title.trim();
+            if !trimmed.is_empty() {
+                header.push_str("Session Title: ");
+                header.push_str(trimmed);
+                header.push('\n');
+            }
+        }
+        if let Some(started) = metadata.get("started_at").and_then(Value::as_str) {
+            header.push_str("Started At: ");
+            header.push_str(started);
+            header.push('\n');
+        }
+        if let Some(ended) = metadata.get("ended_at").and_then(Value::as_str) {
+            header.push_str("Ended At: ");
+            header.push_str(ended);
+            header.push('\n');
+        }
+        if let Some(duration) = metadata.get("duration_formatted").and_then(Value::as_str) {
+            header.push_str("Duration: ");
+            header.push_str(duration);
+            header.push('\n');
+        }
+        header.push('\n');
+    }
+    let metadata_text = |key: &str| {
+        value
+            .get("metadata")
+            .and_then(|metadata| metadata.get(key))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let date = metadata_text("started_at")
+        .map(|raw| {
+            DateTime::parse_from_rfc3339(&raw)
+                .map(|started| started.format("%Y-%m-%d").to_string())
+                .unwrap_or(raw)
+        })
+        .unwrap_or_default();
+
+    let mut lines = Vec::new();
+    let mut participants: Vec<SpeakerInfo> = Vec::new();
+    if let Some(entries) = value.get("transcriptions").and_then(Value::as_array) {
+        for entry in entries {
+            let timestamp = entry
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown time");
+            let speaker = entry
+                .get("speaker")
+                .and_then(|speaker| speaker.get("name"))
+                .and_then(Value::as_str)
+                .unwrap_or("Unknown Speaker");
+            let comment = entry
+                .get("comment")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .trim();
+
+            if comment.is_empty() {
+                continue;
+            }
+
+            let speaker_id = entry
+                .get("speaker")
+                .and_then(|speaker| speaker.get("id"))
+                .and_then(|id| serde_json::from_value::<Option<UserId>>(id.clone()).ok())
+                .flatten();
+            match participants.iter_mut().find(|known| known.name == speaker) {
+                Some(known) => known.id = known.id.or(speaker_id),
+                None => participants.push(SpeakerInfo {
+                    id: speaker_id,
+                    name: speaker.to_string(),
+                }),
+            }
+
+            lines.push(format!(
+                "[{timestamp}] {speaker}: {}\n",
+                redactor.export_text(comment)
+            ));
+        }
+    }
+
+    if lines.is_empty() {
+        bail!("transcript JSON did not contain any caption entries");
+    }
+
+    Ok(FlattenedTranscript {
+        header,
+        lines,
+        participants,
+        title: metadata_text("title"),
+        duration: metadata_text("duration_formatted"),
+        date,
+        template: metadata_text("summary_template"),
+    })
+}
+
+/// Packs whole lines into chunks of at most `max_chars` characters. A single line longer
+/// than the budget is split on character boundaries so multi-byte text never panics.
+fn chunk_lines(lines: &[String], max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for line in lines {
+        let line_chars = line.chars().count();
+        if current_chars + line_chars > max_chars && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if line_chars <= max_chars {
+            current.push_str(line);
+            current_chars += line_chars;
+            continue;
+        }
+
+        let mut piece = String::new();
+        let mut piece_chars = 0;
+        for ch in line.chars() {
+            if piece_chars == max_chars {
+                chunks.push(std::mem::take(&mut piece));
+                piece_chars = 0;
+            }
+            piece.push(ch);
+            piece_chars += 1;
+        }
+        current = piece;
+        current_chars = piece_chars;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Provider settings pointed at `base_url`, for tests against a stub server.
+#[cfg(test)]
+pub(crate) fn test_config(provider: SummaryProvider, base_url: &str) -> SummaryConfig {
+    SummaryConfig {
+        provider,
+        base_url: base_url.to_string(),
+        api_key: Some("test-key".to_string()),
+        model: "test-model".to_string(),
+        max_tokens: 321,
+        chunk_tokens: 4_000,
+        format: SummaryFormat::Markdown,
+        templates: Arc::new(SummaryTemplates::default()),
+        queue: SummaryQueueConfig {
+            path: std::env::temp_dir().join("hammock-test-summary-jobs.json"),
+            max_attempts: 1,
+        },
+        rolling_interval: None,
+    }
+}
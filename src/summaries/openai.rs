@@ -1,104 +1,70 @@
-use std::path::Path;
-
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::{Value, json};
-use tokio::fs;
 
-const RESPONSES_ENDPOINT: &str = "https://api.openai.com/v1/responses";
+use super::{Summarizer, SummaryConfig, SummaryPrompt, read_json_response};
 
-#[derive(Clone)]
-pub struct OpenAiSummarizer {
+/// OpenAI Responses API (`POST {base}/responses`).
+pub struct ResponsesSummarizer {
     client: Client,
-    api_key: String,
+    endpoint: String,
+    api_key: Option<String>,
     model: String,
+    max_tokens: u32,
 }
 
-impl OpenAiSummarizer {
-    pub fn new(api_key: String, model: String) -> Self {
+impl ResponsesSummarizer {
+    pub fn new(config: &SummaryConfig) -> Self {
         Self {
             client: Client::new(),
-            api_key,
-            model,
+            endpoint: format!("{}/responses", config.base_url),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            max_tokens: config.max_tokens,
         }
     }
+}
 
-    pub async fn summarize_transcript(
-        &self,
-        file_path: &Path,
-        session_label: &str,
-    ) -> Result<String> {
-        let transcript_text = self
-            .load_transcript_text(file_path)
-            .await
-            .context("preparing transcript for summary upload")?;
-        self.request_summary(&transcript_text, session_label).await
-    }
-
-    async fn load_transcript_text(&self, file_path: &Path) -> Result<String> {
-        let bytes = fs::read(file_path)
-            .await
-            .with_context(|| format!("reading transcript {}", file_path.display()))?;
-        flatten_transcript(&bytes)
+#[async_trait]
+impl Summarizer for ResponsesSummarizer {
+    fn provider(&self) -> &'static str {
+        "OpenAI Responses"
     }
 
-    async fn request_summary(&self, transcript: &str, session_label: &str) -> Result<String> {
-        let label = if session_label.trim().is_empty() {
-            "Discord session".to_string()
-        } else {
-            session_label.trim().to_string()
-        };
-        let truncated_transcript = truncate_transcript(transcript);
+    async fn complete(&self, prompt: &SummaryPrompt) -> Result<String> {
+        let user_content: Vec<Value> = prompt
+            .user
+            .iter()
+            .map(|text| json!({ "type": "input_text", "text": text }))
+            .collect();
         let payload = json!({
             "model": self.model,
+            "max_output_tokens": self.max_tokens,
             "input": [
                 {
                     "role": "system",
                     "content": [{
                         "type": "input_text",
-                        "text": "You summarize Discord call transcripts into concise meeting notes. Respond with markdown bullet lists, call out action items, and keep the answer under 200 words.",
+                        "text": prompt.system,
                     }]
                 },
                 {
                     "role": "user",
-                    "content": [
-                        {
-                            "type": "input_text",
-                            "text": format!("Summarize the session titled '{label}'."),
-                        },
-                        {
-                            "type": "input_text",
-                            "text": truncated_transcript,
-                        }
-                    ]
+                    "content": user_content,
                 }
             ]
         });
 
-        let response = self
-            .client
-            .post(RESPONSES_ENDPOINT)
-            .bearer_auth(&self.api_key)
-            .json(&payload)
+        let mut request = self.client.post(&self.endpoint).json(&payload);
+        if let Some(key) = self.api_key.as_deref() {
+            request = request.bearer_auth(key);
+        }
+        let response = request
             .send()
             .await
             .context("requesting transcript summary from OpenAI")?;
-
-        let status = response.status();
-        let bytes = response
-            .bytes()
-            .await
-            .context("reading OpenAI summary response body")?;
-
-        if !status.is_success() {
-            let body = String::from_utf8_lossy(&bytes);
-            return Err(anyhow!(
-                "OpenAI summary request failed: status {status}, body: {body}"
-            ));
-        }
-
-        let body: Value =
-            serde_json::from_slice(&bytes).context("parsing OpenAI summary response")?;
+        let body = read_json_response(self.provider(), response).await?;
 
         extract_summary_text(&body)
             .ok_or_else(|| anyhow!("OpenAI summary response did not include text: {}", body))
@@ -133,87 +99,84 @@ fn first_text(value: &Value) -> Option<String> {
     }
 }
 
-fn flatten_transcript(bytes: &[u8]) -> Result<String> {
-    let value: Value = serde_json::from_slice(bytes).context("parsing caption JSON")?;
-
-    let mut buffer = String::with_capacity(bytes.len());
-
-    if let Some(metadata) = value.get("metadata").and_then(Value::as_object) {
-        if let Some(title) = metadata.get("title").and_then(Value::as_str) {
-            let trimmed = title.trim();
-            if !trimmed.is_empty() {
-                buffer.push_str("Session Title: ");
-                buffer.push_str(trimmed);
-                buffer.push('\n');
-            }
-        }
-        if let Some(started) = metadata.get("started_at").and_then(Value::as_str) {
-            buffer.push_str("Started At: ");
-            buffer.push_str(started);
-            buffer.push('\n');
-        }
-        if let Some(ended) = metadata.get("ended_at").and_then(Value::as_str) {
-            buffer.push_str("Ended At: ");
-            buffer.push_str(ended);
-            buffer.push('\n');
-        }
-        if let Some(duration) = metadata.get("duration_formatted").and_then(Value::as_str) {
-            buffer.push_str("Duration: ");
-            buffer.push_str(duration);
-            buffer.push('\n');
-        }
-        buffer.push('\n');
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        summaries::{SummaryProvider, test_config},
+        utils::test_http,
+    };
+
+    #[test]
+    fn reads_output_text_after_reasoning() {
+        let body = json!({
+            "id": "resp_1",
+            "output": [
+                { "type": "reasoning", "id": "rs_1", "summary": [] },
+                {
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [
+                        { "type": "output_text", "text": "  Shipped the release.\n", "annotations": [] }
+                    ]
+                }
+            ]
+        });
+        assert_eq!(
+            extract_summary_text(&body).as_deref(),
+            Some("Shipped the release.")
+        );
     }
 
-    buffer.push_str("Transcript:\n");
-
-    let mut wrote_any = false;
-    if let Some(entries) = value.get("transcriptions").and_then(Value::as_array) {
-        for entry in entries {
-            let timestamp = entry
-                .get("timestamp")
-                .and_then(Value::as_str)
-                .unwrap_or("unknown time");
-            let speaker = entry
-                .get("speaker")
-                .and_then(|speaker| speaker.get("name"))
-                .and_then(Value::as_str)
-                .unwrap_or("Unknown Speaker");
-            let comment = entry
-                .get("comment")
-                .and_then(Value::as_str)
-                .unwrap_or("")
-                .trim();
-
-            if comment.is_empty() {
-                continue;
-            }
-
-            buffer.push('[');
-            buffer.push_str(timestamp);
-            buffer.push_str("] ");
-            buffer.push_str(speaker);
-            buffer.push_str(": ");
-            buffer.push_str(comment);
-            buffer.push('\n');
-            wrote_any = true;
-        }
+    #[test]
+    fn falls_back_to_top_level_content() {
+        let body = json!({ "content": [{ "type": "text", "text": "Notes" }] });
+        assert_eq!(extract_summary_text(&body).as_deref(), Some("Notes"));
     }
 
-    if !wrote_any {
-        bail!("transcript JSON did not contain any caption entries");
+    #[test]
+    fn skips_blank_text() {
+        let body = json!({
+            "output": [{ "type": "message", "content": [{ "type": "output_text", "text": " " }] }]
+        });
+        assert_eq!(extract_summary_text(&body), None);
     }
 
-    Ok(buffer)
-}
-
-fn truncate_transcript(transcript: &str) -> String {
-    const MAX_CHARS: usize = 60_000;
-    if transcript.len() <= MAX_CHARS {
-        return transcript.to_string();
+    #[tokio::test]
+    async fn posts_to_responses_with_bearer_auth() {
+        let reply = json!({
+            "output": [{ "type": "message", "content": [{ "type": "output_text", "text": "Notes" }] }]
+        });
+        let (base_url, mut requests) = test_http::serve(vec![(200, reply.to_string())]).await;
+        let summarizer =
+            ResponsesSummarizer::new(&test_config(SummaryProvider::OpenAiResponses, &base_url));
+        let prompt = SummaryPrompt {
+            system: "Summarize.".to_string(),
+            user: vec!["Ana: shipped it".to_string()],
+        };
+        assert_eq!(summarizer.complete(&prompt).await.unwrap(), "Notes");
+
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/responses");
+        assert_eq!(request.header("authorization"), Some("Bearer test-key"));
+        let body = request.json();
+        assert_eq!(body["max_output_tokens"], 321);
+        assert_eq!(body["model"], "test-model");
     }
 
-    let mut truncated = transcript[..MAX_CHARS].to_string();
-    truncated.push_str("\n\n[Transcript truncated]");
-    truncated
+    #[tokio::test]
+    async fn maps_http_errors() {
+        let (base_url, _requests) =
+            test_http::serve(vec![(429, r#"{"error":"slow down"}"#.to_string())]).await;
+        let summarizer =
+            ResponsesSummarizer::new(&test_config(SummaryProvider::OpenAiResponses, &base_url));
+        let prompt = SummaryPrompt {
+            system: "Summarize.".to_string(),
+            user: vec!["Ana: shipped it".to_string()],
+        };
+        let err = summarizer.complete(&prompt).await.unwrap_err().to_string();
+        assert!(err.contains("status 429"), "{err}");
+        assert!(err.contains("slow down"), "{err}");
+    }
 }
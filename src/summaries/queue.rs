@@ -0,0 +1,232 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId};
+
+/// Delay before the first retry; doubles with every failed attempt.
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+/// Finished jobs are kept this long so `/sessions summary` can still repost to their
+/// original channel.
+const DONE_JOB_TTL_DAYS: i64 = 7;
+
+#[derive(Clone, Debug)]
+pub struct SummaryQueueConfig {
+    pub path: PathBuf,
+    pub max_attempts: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for its first or next attempt.
+    Pending,
+    /// The summary was produced and stored next to the session.
+    Done,
+    /// Every attempt failed; only `/sessions summary retry:true` picks it up again.
+    Failed,
+}
+
+/// A summary owed for a finished session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SummaryJob {
+    pub session_id: String,
+    pub file_name: String,
+    pub guild_id: GuildId,
+    /// Text channel the summary is posted to once it is ready.
+    pub channel_id: ChannelId,
+    pub label: String,
+    pub title: Option<String>,
+    pub status: JobStatus,
+    /// Failed attempts so far.
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl SummaryJob {
+    pub fn new(
+        session_id: String,
+        file_name: String,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        label: String,
+        title: Option<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            session_id,
+            file_name,
+            guild_id,
+            channel_id,
+            label,
+            title,
+            status: JobStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+            completed_at: None,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct QueueFile {
+    jobs: BTreeMap<String, SummaryJob>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: BTreeMap<String, SummaryJob>,
+    /// Jobs with an attempt in flight; never persisted, so a crash mid-attempt leaves
+    /// the job pending for the next run.
+    running: HashSet<String>,
+}
+
+/// Summary jobs keyed by session id, persisted to a JSON file after every change so
+/// provider outages and restarts do not lose summaries.
+pub struct SummaryQueue {
+    config: SummaryQueueConfig,
+    state: Mutex<QueueState>,
+}
+
+impl SummaryQueue {
+    pub fn open(config: SummaryQueueConfig) -> Result<Self> {
+        let file = match fs::read(&config.path) {
+            Ok(bytes) => serde_json::from_slice::<QueueFile>(&bytes)
+                .with_context(|| format!("parsing summary job queue {}", config.path.display()))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => QueueFile::default(),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("reading summary job queue {}", config.path.display())
+                });
+            }
+        };
+        Ok(Self {
+            config,
+            state: Mutex::new(QueueState {
+                jobs: file.jobs,
+                running: HashSet::new(),
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.config.path
+    }
+
+    /// Jobs waiting for an attempt.
+    pub fn pending(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .jobs
+            .values()
+            .filter(|job| job.status == JobStatus::Pending)
+            .count()
+    }
+
+    /// Records a job and returns it claimed, so the caller makes the first attempt.
+    /// Replaces any earlier job for the same session unless one is in flight. A job
+    /// that cannot be persisted is still kept in memory.
+    pub fn enqueue(&self, job: SummaryJob) -> Option<SummaryJob> {
+        let mut state = self.state.lock().unwrap();
+        if state.running.contains(&job.session_id) {
+            return None;
+        }
+        state.running.insert(job.session_id.clone());
+        state.jobs.insert(job.session_id.clone(), job.clone());
+        if let Err(err) = self.persist(&mut state) {
+            tracing::error!(?err, session = %job.session_id, "Failed to persist summary job");
+        }
+        Some(job)
+    }
+
+    /// Claims every pending job whose backoff has elapsed.
+    pub fn claim_due(&self, now: DateTime<Utc>) -> Vec<SummaryJob> {
+        let mut state = self.state.lock().unwrap();
+        let due: Vec<SummaryJob> = state
+            .jobs
+            .values()
+            .filter(|job| job.status == JobStatus::Pending && job.next_attempt_at <= now)
+            .filter(|job| !state.running.contains(&job.session_id))
+            .cloned()
+            .collect();
+        for job in &due {
+            state.running.insert(job.session_id.clone());
+        }
+        due
+    }
+
+    pub fn complete(&self, session_id: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.running.remove(session_id);
+        if let Some(job) = state.jobs.get_mut(session_id) {
+            job.status = JobStatus::Done;
+            job.last_error = None;
+            job.completed_at = Some(Utc::now());
+        }
+        self.persist(&mut state)
+    }
+
+    /// Schedules the next attempt with exponential backoff, or marks the job failed
+    /// once `max_attempts` is reached. Returns the updated job.
+    pub fn fail(&self, session_id: &str, error: &anyhow::Error) -> Result<Option<SummaryJob>> {
+        let mut state = self.state.lock().unwrap();
+        state.running.remove(session_id);
+        let max_attempts = self.config.max_attempts;
+        let Some(job) = state.jobs.get_mut(session_id) else {
+            return Ok(None);
+        };
+        job.attempts += 1;
+        job.last_error = Some(format!("{error:#}"));
+        if job.attempts >= max_attempts {
+            job.status = JobStatus::Failed;
+        } else {
+            job.next_attempt_at = Utc::now() + backoff(job.attempts);
+        }
+        let job = job.clone();
+        self.persist(&mut state)?;
+        Ok(Some(job))
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<SummaryJob> {
+        self.state.lock().unwrap().jobs.get(session_id).cloned()
+    }
+
+    fn persist(&self, state: &mut QueueState) -> Result<()> {
+        let cutoff = Utc::now() - Duration::days(DONE_JOB_TTL_DAYS);
+        state.jobs.retain(|_, job| {
+            job.status != JobStatus::Done || job.completed_at.is_none_or(|done| done > cutoff)
+        });
+        let path = &self.config.path;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let file = QueueFile {
+            jobs: state.jobs.clone(),
+        };
+        let bytes = serde_json::to_vec_pretty(&file)?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes).with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))?;
+        Ok(())
+    }
+}
+
+fn backoff(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    Duration::seconds((BASE_BACKOFF_SECS << exponent).min(MAX_BACKOFF_SECS))
+}
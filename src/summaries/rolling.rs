@@ -0,0 +1,164 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use chrono::DateTime;
+use serenity::{
+    builder::{CreateAllowedMentions, CreateMessage},
+    http::Http,
+    model::id::{ChannelId, GuildId},
+};
+use tokio::{
+    sync::watch,
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior, interval_at},
+};
+
+use crate::captions::{CaptionSink, SessionDocument};
+
+use super::TranscriptSummarizer;
+
+/// Most recent notes passed to the next update as running context.
+const MAX_CONTEXT_CHARS: usize = 1500;
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// Posts incremental notes for a running session every `interval`, each built from the
+/// captions recorded since the previous update.
+pub struct RollingSummaryFeed {
+    interval: Duration,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+pub struct RollingSummaryConfig {
+    pub http: Arc<Http>,
+    pub guild_id: GuildId,
+    pub voice_channel: ChannelId,
+    pub target_channel: ChannelId,
+    pub interval: Duration,
+    pub summarizer: TranscriptSummarizer,
+    pub sink: Arc<CaptionSink>,
+}
+
+/// Progress through the session between updates.
+#[derive(Default)]
+struct FeedState {
+    /// Caption entries already covered by a posted update.
+    processed: usize,
+    context: String,
+}
+
+impl RollingSummaryFeed {
+    pub fn spawn(config: RollingSummaryConfig) -> Self {
+        let (stop, stop_rx) = watch::channel(false);
+        let interval = config.interval;
+        let task = tokio::spawn(run_feed(stop_rx, config));
+        Self {
+            interval,
+            stop,
+            task,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Stops the feed without a final update; `/leave` summarizes the whole session.
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        if let Err(err) = self.task.await {
+            tracing::warn!("rolling summary task join error: {err}");
+        }
+    }
+}
+
+async fn run_feed(mut stop: watch::Receiver<bool>, config: RollingSummaryConfig) {
+    let mut ticker = interval_at(Instant::now() + config.interval, config.interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut state = FeedState::default();
+    loop {
+        tokio::select! {
+            _ = stop.changed() => break,
+            _ = ticker.tick() => {}
+        }
+        // Entries stay unprocessed on failure, so the next update covers them too.
+        if let Err(err) = post_update(&config, &mut state).await {
+            tracing::warn!(
+                ?err,
+                guild = %config.guild_id,
+                channel = %config.target_channel,
+                "Rolling summary update failed"
+            );
+        }
+    }
+}
+
+async fn post_update(config: &RollingSummaryConfig, state: &mut FeedState) -> Result<()> {
+    let Some(session) = config
+        .sink
+        .active_session(config.guild_id, config.voice_channel)
+    else {
+        return Ok(());
+    };
+    let sink = Arc::clone(&config.sink);
+    let file_name = session.file_name.clone();
+    let mut document =
+        tokio::task::spawn_blocking(move || sink.read_session_document(&file_name)).await??;
+    let total = document.transcriptions.len();
+    if total <= state.processed {
+        return Ok(());
+    }
+    document.transcriptions.drain(..state.processed);
+
+    let label = session
+        .title
+        .clone()
+        .unwrap_or_else(|| "this session".to_string());
+    let bytes = serde_json::to_vec(&document)?;
+    let notes = config
+        .summarizer
+        .summarize_increment(&bytes, &label, Some(&state.context))
+        .await?;
+
+    let mut content = format!("**Running notes for {label}**");
+    if let Some(window) = time_window(&document) {
+        content.push_str(&format!(" ({window})"));
+    }
+    content.push('\n');
+    content.push_str(notes.trim());
+    let content: String = content.chars().take(MAX_MESSAGE_CHARS).collect();
+    config
+        .target_channel
+        .send_message(
+            &config.http,
+            CreateMessage::new()
+                .content(content)
+                .allowed_mentions(CreateAllowedMentions::new()),
+        )
+        .await?;
+
+    state.processed = total;
+    state.context.push_str(notes.trim());
+    state.context.push('\n');
+    let excess = state
+        .context
+        .chars()
+        .count()
+        .saturating_sub(MAX_CONTEXT_CHARS);
+    if excess > 0 {
+        state.context = state.context.chars().skip(excess).collect();
+    }
+    Ok(())
+}
+
+/// `HH:MM–HH:MM` span of the entries, in the timestamps' own offset.
+fn time_window(document: &SessionDocument) -> Option<String> {
+    let parse = |raw: &str| DateTime::parse_from_rfc3339(raw).ok();
+    let first = parse(&document.transcriptions.first()?.timestamp)?;
+    let last = parse(&document.transcriptions.last()?.timestamp)?;
+    Some(format!(
+        "{}–{}",
+        first.format("%H:%M"),
+        last.format("%H:%M")
+    ))
+}
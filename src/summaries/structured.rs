@@ -0,0 +1,334 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serenity::{all::CreateEmbed, model::id::UserId};
+
+use crate::{captions::SpeakerInfo, utils::truncate};
+
+pub(super) const SYSTEM_PROMPT: &str = r#"You turn Discord call transcripts into structured meeting notes. Reply with a single JSON object and nothing else, matching this JSON Schema:
+{
+  "type": "object",
+  "additionalProperties": false,
+  "required": ["overview", "topics", "decisions", "action_items", "open_questions"],
+  "properties": {
+    "overview": {"type": "string", "description": "Two or three sentences describing the call."},
+    "topics": {"type": "array", "items": {"type": "string"}, "maxItems": 10},
+    "decisions": {"type": "array", "items": {"type": "string"}, "maxItems": 15},
+    "action_items": {
+      "type": "array",
+      "maxItems": 20,
+      "items": {
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["task", "owner", "owner_id"],
+        "properties": {
+          "task": {"type": "string"},
+          "owner": {"type": ["string", "null"], "description": "Participant name, or null when nobody took it."},
+          "owner_id": {"type": ["string", "null"], "description": "The owner's id from the participant list, or null."}
+        }
+      }
+    },
+    "open_questions": {"type": "array", "items": {"type": "string"}, "maxItems": 10}
+  }
+}
+Only assign owners who appear in the participant list. Use empty arrays when a section has nothing to report."#;
+
+const MAX_TOPICS: usize = 10;
+const MAX_DECISIONS: usize = 15;
+const MAX_ACTION_ITEMS: usize = 20;
+const MAX_OPEN_QUESTIONS: usize = 10;
+/// Discord caps embed field values at 1024 characters.
+const EMBED_FIELD_LIMIT: usize = 1024;
+const EMBED_DESCRIPTION_LIMIT: usize = 4096;
+const EMBED_TITLE_LIMIT: usize = 256;
+/// Discord rejects embeds whose title, description, and fields add up to more than this.
+const EMBED_TOTAL_LIMIT: usize = 6000;
+const EMBED_COLOR: u32 = 0x5865F2;
+
+/// Meeting notes produced by the structured summary mode and stored in the session
+/// document's `summary` field.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StructuredSummary {
+    pub overview: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[serde(default)]
+    pub action_items: Vec<ActionItem>,
+    #[serde(default)]
+    pub open_questions: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub task: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Discord user of the owner, only set when it matches a transcript speaker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<UserId>,
+}
+
+/// The model's reply as described by [`SYSTEM_PROMPT`]; unknown or missing fields fail
+/// deserialization.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSummary {
+    overview: String,
+    topics: Vec<String>,
+    decisions: Vec<String>,
+    action_items: Vec<RawActionItem>,
+    open_questions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawActionItem {
+    task: String,
+    owner: Option<String>,
+    owner_id: Option<String>,
+}
+
+/// Lists the speakers the model may assign action items to.
+pub(super) fn participant_block(participants: &[SpeakerInfo]) -> String {
+    let mut block = String::from("Participants:\n");
+    for speaker in participants {
+        match speaker.id {
+            Some(id) => block.push_str(&format!("- {} (id: {})\n", speaker.name, id.get())),
+            None => block.push_str(&format!("- {} (id: null)\n", speaker.name)),
+        }
+    }
+    block
+}
+
+impl StructuredSummary {
+    /// Parses and validates a model reply. Owners are resolved against `participants`
+    /// by id first and then by name; anything that does not match keeps only its name.
+    pub fn parse(reply: &str, participants: &[SpeakerInfo]) -> Result<Self> {
+        let json = extract_object(reply).context("reply did not contain a JSON object")?;
+        let raw: RawSummary =
+            serde_json::from_str(json).context("reply did not match the schema")?;
+
+        let overview = raw.overview.trim().to_string();
+        if overview.is_empty() {
+            bail!("overview must not be empty");
+        }
+        let action_items = raw
+            .action_items
+            .into_iter()
+            .filter_map(|item| {
+                let task = item.task.trim().to_string();
+                if task.is_empty() {
+                    return None;
+                }
+                let speaker = resolve_owner(
+                    item.owner_id.as_deref(),
+                    item.owner.as_deref(),
+                    participants,
+                );
+                let owner = speaker
+                    .map(|speaker| speaker.name.clone())
+                    .or_else(|| clean(item.owner.as_deref()));
+                Some(ActionItem {
+                    task,
+                    owner,
+                    owner_id: speaker.and_then(|speaker| speaker.id),
+                })
+            })
+            .take(MAX_ACTION_ITEMS)
+            .collect();
+
+        Ok(Self {
+            overview,
+            topics: clean_list(raw.topics, MAX_TOPICS),
+            decisions: clean_list(raw.decisions, MAX_DECISIONS),
+            action_items,
+            open_questions: clean_list(raw.open_questions, MAX_OPEN_QUESTIONS),
+        })
+    }
+
+    /// Markdown rendering for plain-text consumers such as webhooks.
+    pub fn to_markdown(&self) -> String {
+        let mut buffer = format!("{}\n", self.overview);
+        push_section(&mut buffer, "Decisions", self.decisions.iter().cloned());
+        push_section(
+            &mut buffer,
+            "Action items",
+            self.action_items.iter().map(|item| item.render(false)),
+        );
+        push_section(
+            &mut buffer,
+            "Open questions",
+            self.open_questions.iter().cloned(),
+        );
+        push_section(&mut buffer, "Topics", self.topics.iter().cloned());
+        buffer
+    }
+
+    /// Discord embed with owners rendered as user mentions. Mentions inside embeds do
+    /// not ping anyone. Sections are shortened, and the last ones dropped, to stay within
+    /// Discord's total embed size; [`Self::to_markdown`] has the full text.
+    pub fn to_embed(&self, label: &str) -> CreateEmbed {
+        let title = truncate(&format!("Summary for {label}"), EMBED_TITLE_LIMIT);
+        let mut remaining = EMBED_TOTAL_LIMIT - title.chars().count();
+        let description = truncate(&self.overview, EMBED_DESCRIPTION_LIMIT.min(remaining));
+        remaining -= description.chars().count();
+        let mut embed = CreateEmbed::new()
+            .title(title)
+            .description(description)
+            .color(EMBED_COLOR);
+        let sections = [
+            ("Decisions", bullet_list(self.decisions.iter().cloned())),
+            (
+                "Action items",
+                bullet_list(self.action_items.iter().map(|item| item.render(true))),
+            ),
+            (
+                "Open questions",
+                bullet_list(self.open_questions.iter().cloned()),
+            ),
+            ("Topics", bullet_list(self.topics.iter().cloned())),
+        ];
+        for (name, value) in sections {
+            if value.is_empty() {
+                continue;
+            }
+            // Field names count toward the total too; skip sections with no room left.
+            let Some(room) = remaining
+                .checked_sub(name.chars().count())
+                .filter(|room| *room > 1)
+            else {
+                break;
+            };
+            let value = truncate(&value, EMBED_FIELD_LIMIT.min(room));
+            remaining = room - value.chars().count();
+            embed = embed.field(name, value, false);
+        }
+        embed
+    }
+}
+
+impl ActionItem {
+    fn render(&self, mention: bool) -> String {
+        let owner = match (self.owner_id, self.owner.as_deref()) {
+            (Some(id), _) if mention => format!("<@{}>", id.get()),
+            (_, Some(name)) => format!("**{name}**"),
+            (Some(id), None) => format!("user {}", id.get()),
+            (None, None) => return self.task.clone(),
+        };
+        format!("{} — {owner}", self.task)
+    }
+}
+
+fn resolve_owner<'a>(
+    owner_id: Option<&str>,
+    owner: Option<&str>,
+    participants: &'a [SpeakerInfo],
+) -> Option<&'a SpeakerInfo> {
+    let by_id = owner_id
+        .map(str::trim)
+        .and_then(|raw| raw.parse::<u64>().ok())
+        .and_then(|id| {
+            participants
+                .iter()
+                .find(|speaker| speaker.id.is_some_and(|known| known.get() == id))
+        });
+    by_id.or_else(|| {
+        let name = owner?.trim();
+        participants
+            .iter()
+            .find(|speaker| speaker.name.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Strips code fences or surrounding prose that some models add despite instructions.
+fn extract_object(reply: &str) -> Option<&str> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    (end > start).then(|| &reply[start..=end])
+}
+
+fn clean(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty() && !value.eq_ignore_ascii_case("null"))
+        .map(str::to_string)
+}
+
+fn clean_list(items: Vec<String>, limit: usize) -> Vec<String> {
+    items
+        .into_iter()
+        .filter_map(|item| clean(Some(&item)))
+        .take(limit)
+        .collect()
+}
+
+fn push_section(buffer: &mut String, heading: &str, items: impl Iterator<Item = String>) {
+    let list = bullet_list(items);
+    if !list.is_empty() {
+        buffer.push_str(&format!("\n**{heading}**\n{list}"));
+    }
+}
+
+fn bullet_list(items: impl Iterator<Item = String>) -> String {
+    items.map(|item| format!("- {item}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embed_length(embed: &CreateEmbed) -> usize {
+        let value = serde_json::to_value(embed).unwrap();
+        let count =
+            |value: &serde_json::Value| value.as_str().map_or(0, |text| text.chars().count());
+        count(&value["title"])
+            + count(&value["description"])
+            + value["fields"].as_array().map_or(0, |fields| {
+                fields
+                    .iter()
+                    .map(|field| count(&field["name"]) + count(&field["value"]))
+                    .sum()
+            })
+    }
+
+    #[test]
+    fn embed_stays_within_discord_total() {
+        let long = "x".repeat(900);
+        let summary = StructuredSummary {
+            overview: "o".repeat(5000),
+            topics: vec![long.clone(); MAX_TOPICS],
+            decisions: vec![long.clone(); MAX_DECISIONS],
+            action_items: vec![
+                ActionItem {
+                    task: long.clone(),
+                    owner: Some("Ana".to_string()),
+                    owner_id: None,
+                };
+                MAX_ACTION_ITEMS
+            ],
+            open_questions: vec![long; MAX_OPEN_QUESTIONS],
+        };
+        let embed = summary.to_embed(&"L".repeat(300));
+        assert!(embed_length(&embed) <= EMBED_TOTAL_LIMIT);
+    }
+
+    #[test]
+    fn short_summaries_keep_every_section() {
+        let summary = StructuredSummary {
+            overview: "Planned the release.".to_string(),
+            topics: vec!["Release".to_string()],
+            decisions: vec!["Ship Friday".to_string()],
+            action_items: vec![ActionItem {
+                task: "Tag the build".to_string(),
+                owner: Some("Ana".to_string()),
+                owner_id: None,
+            }],
+            open_questions: vec!["Who announces it?".to_string()],
+        };
+        let value = serde_json::to_value(summary.to_embed("standup")).unwrap();
+        assert_eq!(value["description"], "Planned the release.");
+        assert_eq!(value["fields"].as_array().unwrap().len(), 4);
+        assert_eq!(value["fields"][1]["value"], "- Tag the build — **Ana**\n");
+    }
+}
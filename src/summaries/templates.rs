@@ -0,0 +1,195 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serenity::model::id::GuildId;
+
+use super::SummaryFormat;
+
+/// Name of the template used when neither the session nor the guild picks one.
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+const DEFAULT_SYSTEM_PROMPT: &str = "You summarize Discord call transcripts into concise meeting notes. Respond with markdown bullet lists and call out action items.";
+const DEFAULT_MAX_WORDS: u32 = 200;
+
+/// A named summary prompt. `system` may reference `{title}`, `{participants}`,
+/// `{duration}`, and `{date}`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SummaryTemplate {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub system: String,
+    #[serde(default)]
+    pub max_words: Option<u32>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Overrides `SUMMARY_FORMAT` for this template.
+    #[serde(default)]
+    pub format: Option<SummaryFormat>,
+    /// Guilds allowed to use the template; empty means every guild.
+    #[serde(default)]
+    pub guilds: Vec<GuildId>,
+}
+
+/// Values substituted into a template's system prompt.
+pub struct TemplateVars<'a> {
+    pub title: &'a str,
+    pub participants: &'a str,
+    pub duration: &'a str,
+    pub date: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateFile {
+    #[serde(default)]
+    templates: BTreeMap<String, SummaryTemplate>,
+    /// Guild id → template name used when a session does not pick one.
+    #[serde(default)]
+    guild_defaults: HashMap<String, String>,
+}
+
+/// Templates loaded from `SUMMARY_TEMPLATES_PATH`, plus the built-in `default`.
+#[derive(Clone, Debug)]
+pub struct SummaryTemplates {
+    templates: BTreeMap<String, SummaryTemplate>,
+    guild_defaults: HashMap<GuildId, String>,
+}
+
+impl Default for SummaryTemplates {
+    fn default() -> Self {
+        let mut templates = BTreeMap::new();
+        templates.insert(
+            DEFAULT_TEMPLATE.to_string(),
+            SummaryTemplate {
+                description: Some("Concise meeting notes with action items".to_string()),
+                system: DEFAULT_SYSTEM_PROMPT.to_string(),
+                max_words: Some(DEFAULT_MAX_WORDS),
+                language: None,
+                format: None,
+                guilds: Vec::new(),
+            },
+        );
+        Self {
+            templates,
+            guild_defaults: HashMap::new(),
+        }
+    }
+}
+
+impl SummaryTemplates {
+    /// Reads a template file. Its templates are added to (or replace) the built-in
+    /// `default`; template names are case-insensitive.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("reading summary templates from {}", path.display()))?;
+        let file: TemplateFile = serde_json::from_str(&raw)
+            .with_context(|| format!("parsing summary templates in {}", path.display()))?;
+
+        let mut registry = Self::default();
+        for (name, template) in file.templates {
+            let name = normalize(&name);
+            if name.is_empty() || name.len() > 100 {
+                bail!("summary template names must be 1-100 characters");
+            }
+            if template.system.trim().is_empty() {
+                bail!("summary template '{name}' has an empty system prompt");
+            }
+            registry.templates.insert(name, template);
+        }
+        for (guild, name) in file.guild_defaults {
+            let guild_id = guild
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|id| *id > 0)
+                .map(GuildId::new)
+                .ok_or_else(|| anyhow!("invalid guild id '{guild}' in guild_defaults"))?;
+            let name = normalize(&name);
+            let template = registry.templates.get(&name).ok_or_else(|| {
+                anyhow!("guild_defaults for {guild_id} names unknown template '{name}'")
+            })?;
+            if !template.allows(guild_id) {
+                bail!("guild_defaults for {guild_id} names template '{name}', which excludes it");
+            }
+            registry.guild_defaults.insert(guild_id, name);
+        }
+        Ok(registry)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Templates usable in `guild_id`, sorted by name.
+    pub fn available(&self, guild_id: GuildId) -> impl Iterator<Item = (&str, &SummaryTemplate)> {
+        self.templates
+            .iter()
+            .filter(move |(_, template)| template.allows(guild_id))
+            .map(|(name, template)| (name.as_str(), template))
+    }
+
+    /// Picks the requested template, falling back to the guild default and then to
+    /// `default`. Errors carry a user-facing message when `requested` is unknown or not
+    /// available in the guild.
+    pub fn resolve(
+        &self,
+        guild_id: Option<GuildId>,
+        requested: Option<&str>,
+    ) -> Result<(&str, &SummaryTemplate)> {
+        if let Some(requested) = requested.map(normalize).filter(|name| !name.is_empty()) {
+            return match self.templates.get_key_value(&requested) {
+                Some((name, template)) if guild_id.is_none_or(|id| template.allows(id)) => {
+                    Ok((name.as_str(), template))
+                }
+                _ => Err(anyhow!("Unknown summary template '{requested}'")),
+            };
+        }
+        let fallback = guild_id
+            .and_then(|id| self.guild_defaults.get(&id))
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_TEMPLATE);
+        self.templates
+            .get_key_value(fallback)
+            .map(|(name, template)| (name.as_str(), template))
+            .ok_or_else(|| anyhow!("summary template '{fallback}' is not configured"))
+    }
+}
+
+impl SummaryTemplate {
+    fn allows(&self, guild_id: GuildId) -> bool {
+        self.guilds.is_empty() || self.guilds.contains(&guild_id)
+    }
+
+    /// The system prompt with variables substituted and the length and language
+    /// instructions appended.
+    pub fn render(&self, vars: &TemplateVars<'_>) -> String {
+        let mut prompt = self
+            .system
+            .replace("{title}", vars.title)
+            .replace("{participants}", vars.participants)
+            .replace("{duration}", vars.duration)
+            .replace("{date}", vars.date);
+        if let Some(language) = self
+            .language
+            .as_deref()
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+        {
+            prompt.push_str(&format!(" Write the summary in {language}."));
+        }
+        if let Some(max_words) = self.max_words {
+            prompt.push_str(&format!(" Keep the answer under {max_words} words."));
+        }
+        prompt
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_ascii_lowercase()
+}
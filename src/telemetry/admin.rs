@@ -0,0 +1,543 @@
+use std::sync::Arc;
+
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::{
+    ActiveCall, JoinRequest,
+    captions::index::{IndexedSession, session_id_from_file_name},
+};
+
+use super::server::HttpAppState;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    handle_list_calls,
+    handle_join,
+    handle_leave,
+    handle_retitle,
+    handle_list_sessions,
+    handle_get_session
+))]
+pub(super) struct AdminApi;
+
+/// Bearer-protected session control endpoints, mounted under `/api`.
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/calls", web::get().to(handle_list_calls))
+        .route("/api/guilds/{guild_id}/join", web::post().to(handle_join))
+        .route("/api/guilds/{guild_id}/leave", web::post().to(handle_leave))
+        .route(
+            "/api/guilds/{guild_id}/title",
+            web::put().to(handle_retitle),
+        )
+        .route("/api/sessions", web::get().to(handle_list_sessions))
+        .route(
+            "/api/sessions/{session_id}",
+            web::get().to(handle_get_session),
+        );
+}
+
+/// Discord ids arrive as strings (JavaScript cannot hold a snowflake in a number), but
+/// plain integers are accepted too.
+#[derive(Deserialize, ToSchema)]
+#[serde(untagged)]
+enum Snowflake {
+    Text(String),
+    Number(u64),
+}
+
+impl Snowflake {
+    fn get(&self) -> Option<u64> {
+        match self {
+            Self::Text(raw) => raw.trim().parse().ok(),
+            Self::Number(value) => Some(*value),
+        }
+        .filter(|value| *value > 0)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct CallResponse {
+    guild_id: String,
+    channel_id: String,
+    participants: usize,
+    live_channel_id: Option<String>,
+    session_id: Option<String>,
+    title: Option<String>,
+    started_at: Option<String>,
+}
+
+impl From<ActiveCall> for CallResponse {
+    fn from(call: ActiveCall) -> Self {
+        let session = call.session.as_ref();
+        Self {
+            guild_id: call.guild_id.to_string(),
+            channel_id: call.channel_id.to_string(),
+            participants: call.participants,
+            live_channel_id: call.live_channel.map(|id| id.to_string()),
+            session_id: session
+                .and_then(|session| session_id_from_file_name(&session.file_name))
+                .map(|(id, _)| id),
+            title: session.and_then(|session| session.title.clone()),
+            started_at: session.map(|session| session.started_at.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct CallsResponse {
+    calls: Vec<CallResponse>,
+}
+
+/// List active calls
+///
+/// Voice channels currently being captioned, with participant counts, live caption
+/// targets, and the running session.
+#[utoipa::path(
+    get,
+    path = "/api/calls",
+    tag = "control",
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Active calls", body = CallsResponse),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured")
+    )
+)]
+async fn handle_list_calls(req: HttpRequest, state: web::Data<HttpAppState>) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    let calls = state
+        .bot_state
+        .active_calls()
+        .into_iter()
+        .map(CallResponse::from)
+        .collect();
+    HttpResponse::Ok().json(CallsResponse { calls })
+}
+
+fn call_for_guild(state: &HttpAppState, guild_id: GuildId) -> Option<ActiveCall> {
+    state
+        .bot_state
+        .active_calls()
+        .into_iter()
+        .find(|call| call.guild_id == guild_id)
+}
+
+fn parse_guild(raw: &str) -> Result<GuildId, HttpResponse> {
+    raw.trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|id| *id > 0)
+        .map(GuildId::new)
+        .ok_or_else(|| HttpResponse::BadRequest().body("guild_id must be a Discord snowflake"))
+}
+
+#[derive(Deserialize, ToSchema)]
+struct JoinBody {
+    channel_id: Snowflake,
+    title: Option<String>,
+    live_channel_id: Option<Snowflake>,
+    /// Text channel the transcript and summary are posted to when the session ends.
+    text_channel_id: Option<Snowflake>,
+    /// Summary template for the session (see `SUMMARY_TEMPLATES_PATH`).
+    template: Option<String>,
+}
+
+/// Force join a voice channel
+///
+/// Starts captioning like `/join`.
+#[utoipa::path(
+    post,
+    path = "/api/guilds/{guild_id}/join",
+    tag = "control",
+    params(("guild_id" = String, Path, description = "Guild snowflake")),
+    request_body = JoinBody,
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Joined; returns the active call", body = CallResponse),
+        (status = 400, description = "Invalid guild or channel id"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 409, description = "Already captioning in this guild"),
+        (status = 502, description = "Discord or the voice client rejected the join"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured or the gateway is not connected")
+    )
+)]
+async fn handle_join(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    path: web::Path<String>,
+    body: web::Json<JoinBody>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    let guild_id = match parse_guild(&path) {
+        Ok(guild_id) => guild_id,
+        Err(response) => return response,
+    };
+    let body = body.into_inner();
+    let Some(channel_id) = body.channel_id.get().map(ChannelId::new) else {
+        return HttpResponse::BadRequest().body("channel_id must be a Discord snowflake");
+    };
+    let live_channel = match body.live_channel_id.as_ref().map(Snowflake::get) {
+        Some(None) => {
+            return HttpResponse::BadRequest().body("live_channel_id must be a Discord snowflake");
+        }
+        other => other.flatten().map(ChannelId::new),
+    };
+    let text_channel = match body.text_channel_id.as_ref().map(Snowflake::get) {
+        Some(None) => {
+            return HttpResponse::BadRequest().body("text_channel_id must be a Discord snowflake");
+        }
+        other => other.flatten().map(ChannelId::new),
+    };
+    let Some(ctx) = state.bot_state.discord_context() else {
+        return HttpResponse::ServiceUnavailable().body("Discord gateway is not connected yet");
+    };
+    if let Some(call) = call_for_guild(&state, guild_id) {
+        return HttpResponse::Conflict().body(format!(
+            "Already captioning channel {} in this guild; leave first",
+            call.channel_id
+        ));
+    }
+
+    let request = JoinRequest {
+        guild_id,
+        channel_id,
+        title: body
+            .title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty()),
+        live_channel,
+        summary_template: body.template,
+        text_channel,
+        rolling_summary_minutes: None,
+    };
+    if let Err(err) = state.bot_state.join_voice(&ctx, request).await {
+        tracing::warn!(?err, guild = %guild_id, channel = %channel_id, "Admin join failed");
+        return HttpResponse::BadGateway().body(err.to_string());
+    }
+    tracing::info!(guild = %guild_id, channel = %channel_id, "Joined voice via admin API");
+
+    match call_for_guild(&state, guild_id) {
+        Some(call) => HttpResponse::Ok().json(CallResponse::from(call)),
+        None => HttpResponse::InternalServerError().body("Joined, but the call is not tracked"),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct LeaveResponse {
+    guild_id: String,
+    session_id: Option<String>,
+    title: Option<String>,
+    duration_seconds: Option<u64>,
+}
+
+/// Force leave voice
+///
+/// Stops captioning like `/leave` and finalizes the session file. Nothing is posted to
+/// Discord.
+#[utoipa::path(
+    post,
+    path = "/api/guilds/{guild_id}/leave",
+    tag = "control",
+    params(("guild_id" = String, Path, description = "Guild snowflake")),
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Left; returns the finished session", body = LeaveResponse),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not captioning in this guild"),
+        (status = 502, description = "The voice client failed to disconnect"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured or the gateway is not connected")
+    )
+)]
+async fn handle_leave(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    let guild_id = match parse_guild(&path) {
+        Ok(guild_id) => guild_id,
+        Err(response) => return response,
+    };
+    let Some(ctx) = state.bot_state.discord_context() else {
+        return HttpResponse::ServiceUnavailable().body("Discord gateway is not connected yet");
+    };
+    if call_for_guild(&state, guild_id).is_none() {
+        return HttpResponse::NotFound().body("Not captioning in this guild");
+    }
+
+    match state
+        .bot_state
+        .leave_unattended(&ctx, guild_id, "admin API")
+        .await
+    {
+        Ok(summary) => {
+            tracing::info!(guild = %guild_id, "Left voice via admin API");
+            HttpResponse::Ok().json(LeaveResponse {
+                guild_id: guild_id.to_string(),
+                session_id: summary.as_ref().map(|summary| summary.session_id()),
+                title: summary.as_ref().and_then(|summary| summary.title.clone()),
+                duration_seconds: summary.as_ref().map(|summary| summary.duration.as_secs()),
+            })
+        }
+        Err(err) => {
+            tracing::warn!(?err, guild = %guild_id, "Admin leave failed");
+            HttpResponse::BadGateway().body(err.to_string())
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct RetitleBody {
+    title: Option<String>,
+}
+
+/// Retitle the active session
+///
+/// A null or empty title clears it. The session file name is not changed.
+#[utoipa::path(
+    put,
+    path = "/api/guilds/{guild_id}/title",
+    tag = "control",
+    params(("guild_id" = String, Path, description = "Guild snowflake")),
+    request_body = RetitleBody,
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Updated active call", body = CallResponse),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Not captioning in this guild"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured")
+    )
+)]
+async fn handle_retitle(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    path: web::Path<String>,
+    body: web::Json<RetitleBody>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    let guild_id = match parse_guild(&path) {
+        Ok(guild_id) => guild_id,
+        Err(response) => return response,
+    };
+    let title = body.into_inner().title;
+    match state.bot_state.retitle_session(guild_id, title).await {
+        Ok(true) => match call_for_guild(&state, guild_id) {
+            Some(call) => HttpResponse::Ok().json(CallResponse::from(call)),
+            None => HttpResponse::NotFound().body("Not captioning in this guild"),
+        },
+        Ok(false) => HttpResponse::NotFound().body("Not captioning in this guild"),
+        Err(err) => {
+            tracing::error!(?err, guild = %guild_id, "Failed to retitle session");
+            HttpResponse::InternalServerError().body("Failed to update the session file")
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SessionListParams {
+    guild_id: u64,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct SessionListItem {
+    id: String,
+    guild_id: String,
+    channel_id: String,
+    title: Option<String>,
+    started_at: Option<String>,
+    ended_at: Option<String>,
+    duration: Option<String>,
+    participants: Vec<String>,
+    lines: usize,
+    archived: bool,
+}
+
+impl From<&IndexedSession> for SessionListItem {
+    fn from(session: &IndexedSession) -> Self {
+        Self {
+            id: session.id.clone(),
+            guild_id: session.guild_id.to_string(),
+            channel_id: session.channel_id.to_string(),
+            title: session.title.clone(),
+            started_at: session.started_at.map(|value| value.to_rfc3339()),
+            ended_at: session.ended_at.map(|value| value.to_rfc3339()),
+            duration: session.duration_formatted.clone(),
+            participants: session.participants.clone(),
+            lines: session.lines.len(),
+            archived: session.archived,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct SessionListResponse {
+    sessions: Vec<SessionListItem>,
+}
+
+/// List finished sessions
+///
+/// Stored sessions for one guild, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/sessions",
+    tag = "sessions",
+    params(SessionListParams),
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Sessions", body = SessionListResponse),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured")
+    )
+)]
+async fn handle_list_sessions(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    params: web::Query<SessionListParams>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    if params.guild_id == 0 {
+        return HttpResponse::BadRequest().body("guild_id must be a Discord snowflake");
+    }
+    let guild_id = GuildId::new(params.guild_id);
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let index = state.bot_state.session_index();
+    let result = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        Ok::<_, anyhow::Error>(index.sessions_for_guild(guild_id))
+    })
+    .await;
+    match result {
+        Ok(Ok(sessions)) => HttpResponse::Ok().json(SessionListResponse {
+            sessions: sessions
+                .iter()
+                .take(limit)
+                .map(SessionListItem::from)
+                .collect(),
+        }),
+        Ok(Err(err)) => {
+            tracing::error!(?err, "Session index refresh failed");
+            HttpResponse::InternalServerError().body("Failed to refresh session index")
+        }
+        Err(err) => {
+            tracing::error!("session list task join error: {err}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum DocumentFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SessionGetParams {
+    #[serde(default)]
+    format: DocumentFormat,
+    #[serde(default)]
+    download: bool,
+}
+
+/// Fetch a session document
+///
+/// Returns the session as JSON (default) or plain text with export redaction applied.
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{session_id}",
+    tag = "sessions",
+    params(("session_id" = String, Path, description = "Session id"), SessionGetParams),
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Session document", content(
+            ("application/json"),
+            ("text/plain")
+        )),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Unknown session id"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured")
+    )
+)]
+async fn handle_get_session(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    path: web::Path<String>,
+    params: web::Query<SessionGetParams>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    let session_id = path.into_inner();
+    let bot_state = Arc::clone(&state.bot_state);
+    let lookup_id = session_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let index = bot_state.session_index();
+        index.refresh()?;
+        let Some(session) = index.get(&lookup_id) else {
+            return Ok(None);
+        };
+        let mut document = bot_state
+            .caption_sink
+            .read_session_document(&session.file_name)?;
+        bot_state
+            .redactor()
+            .redact_document_for_export(&mut document);
+        Ok::<_, anyhow::Error>(Some(document))
+    })
+    .await;
+
+    let document = match result {
+        Ok(Ok(Some(document))) => document,
+        Ok(Ok(None)) => return HttpResponse::NotFound().body("Unknown session id"),
+        Ok(Err(err)) => {
+            tracing::error!(?err, session = %session_id, "Failed to load session document");
+            return HttpResponse::InternalServerError().body("Failed to read session from storage");
+        }
+        Err(err) => {
+            tracing::error!("session fetch task join error: {err}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let (body, content_type, extension) = match params.format {
+        DocumentFormat::Json => match serde_json::to_vec(&document) {
+            Ok(bytes) => (bytes, "application/json", "json"),
+            Err(err) => {
+                tracing::error!(?err, "Failed to serialize session document");
+                return HttpResponse::InternalServerError().finish();
+            }
+        },
+        DocumentFormat::Text => (
+            document.render_text().into_bytes(),
+            "text/plain; charset=utf-8",
+            "txt",
+        ),
+    };
+    let mut response = HttpResponse::Ok();
+    response.content_type(content_type);
+    if params.download {
+        response.insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{session_id}.{extension}\""),
+        ));
+    }
+    response.body(body)
+}
@@ -1,16 +1,18 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     sync::{
         Mutex,
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
     },
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use chrono::{TimeZone, Utc};
 use serde::Serialize;
+use serenity::model::id::GuildId;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LineWindowSnapshot {
     pub last_1h: usize,
     pub last_30m: usize,
@@ -20,21 +22,113 @@ pub struct LineWindowSnapshot {
     pub last_30s: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct RetentionRunSnapshot {
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub action: String,
+    pub examined: usize,
+    pub deleted: usize,
+    pub archived: usize,
+    pub bytes_freed: u64,
+    pub errors: usize,
+    pub affected_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MetricsSnapshot {
     pub uptime_seconds: u64,
     pub total_transcribed_lines: u64,
     pub total_sessions_started: u64,
     pub total_sessions_completed: u64,
+    pub total_redactions: u64,
     pub last_transcription_at: Option<String>,
     pub line_windows: LineWindowSnapshot,
+    pub last_retention_run: Option<RetentionRunSnapshot>,
+    pub transcription_performance: Vec<TranscriptionPerformance>,
+}
+
+/// Timings for one Whisper job.
+#[derive(Clone, Copy, Debug)]
+pub struct JobTiming {
+    pub queue_wait: Duration,
+    pub audio_length: Duration,
+    pub inference: Duration,
+}
+
+impl JobTiming {
+    /// Inference time divided by audio length; above 1.0 the worker is falling behind.
+    pub fn real_time_factor(&self) -> f64 {
+        let audio = self.audio_length.as_secs_f64();
+        if audio <= 0.0 {
+            return 0.0;
+        }
+        self.inference.as_secs_f64() / audio
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
 }
 
+impl Percentiles {
+    fn from_samples(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(f64::total_cmp);
+        let pick = |quantile: f64| {
+            let rank = (quantile * values.len() as f64).ceil() as usize;
+            values[rank.clamp(1, values.len()) - 1]
+        };
+        Self {
+            p50: pick(0.5),
+            p90: pick(0.9),
+            p99: pick(0.99),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// Per guild and model transcription performance. Totals cover the whole process
+/// lifetime; percentiles cover the most recent `PERFORMANCE_SAMPLES` jobs.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct TranscriptionPerformance {
+    pub guild_id: String,
+    pub model: String,
+    pub jobs: u64,
+    pub audio_seconds_total: f64,
+    pub queue_wait_seconds_total: f64,
+    pub inference_seconds_total: f64,
+    /// Lifetime inference time divided by lifetime audio length.
+    pub real_time_factor: f64,
+    /// Sum of every job's own real-time factor, for the Prometheus summary.
+    pub real_time_factor_sum: f64,
+    pub audio_seconds: Percentiles,
+    pub queue_wait_seconds: Percentiles,
+    pub inference_seconds: Percentiles,
+    pub real_time_factor_percentiles: Percentiles,
+}
+
+/// Upper bounds (seconds) for Whisper inference time per chunk.
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0];
+/// Upper bounds (seconds) for the audio length of a chunk handed to Whisper.
+const CHUNK_LENGTH_BUCKETS: &[f64] = &[0.5, 1.0, 2.0, 3.0, 5.0, 8.0, 13.0, 21.0, 30.0];
+/// Upper bounds (seconds) for the time a chunk waits in the transcription queue.
+const QUEUE_WAIT_BUCKETS: &[f64] = &[0.001, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 15.0, 60.0];
+/// Recent jobs kept per guild/model series for percentile calculations.
+const PERFORMANCE_SAMPLES: usize = 512;
+
 pub struct AppMetrics {
     start_time: Instant,
     total_transcribed_lines: AtomicU64,
     total_sessions_started: AtomicU64,
     total_sessions_completed: AtomicU64,
+    total_redactions: AtomicU64,
     last_transcription_epoch: AtomicU64,
     window_1h: LineWindow,
     window_30m: LineWindow,
@@ -42,6 +136,16 @@ pub struct AppMetrics {
     window_5m: LineWindow,
     window_1m: LineWindow,
     window_30s: LineWindow,
+    last_retention_run: Mutex<Option<RetentionRunSnapshot>>,
+    transcription_latency: Histogram,
+    chunk_length: Histogram,
+    queue_wait: Histogram,
+    performance: Mutex<HashMap<(GuildId, String), PerformanceSeries>>,
+    gateway_connected: AtomicBool,
+    /// Milliseconds since `start_time` (plus one) when the in-flight job started; 0 when idle.
+    job_started_ms: AtomicU64,
+    /// Milliseconds since `start_time` when the worker last finished a job.
+    last_job_finished_ms: AtomicU64,
 }
 
 impl AppMetrics {
@@ -51,6 +155,7 @@ impl AppMetrics {
             total_transcribed_lines: AtomicU64::new(0),
             total_sessions_started: AtomicU64::new(0),
             total_sessions_completed: AtomicU64::new(0),
+            total_redactions: AtomicU64::new(0),
             last_transcription_epoch: AtomicU64::new(0),
             window_1h: LineWindow::new(Duration::from_secs(60 * 60)),
             window_30m: LineWindow::new(Duration::from_secs(30 * 60)),
@@ -58,6 +163,14 @@ impl AppMetrics {
             window_5m: LineWindow::new(Duration::from_secs(5 * 60)),
             window_1m: LineWindow::new(Duration::from_secs(60)),
             window_30s: LineWindow::new(Duration::from_secs(30)),
+            last_retention_run: Mutex::new(None),
+            transcription_latency: Histogram::new(LATENCY_BUCKETS),
+            chunk_length: Histogram::new(CHUNK_LENGTH_BUCKETS),
+            queue_wait: Histogram::new(QUEUE_WAIT_BUCKETS),
+            performance: Mutex::new(HashMap::new()),
+            gateway_connected: AtomicBool::new(false),
+            job_started_ms: AtomicU64::new(0),
+            last_job_finished_ms: AtomicU64::new(0),
         }
     }
 
@@ -88,6 +201,86 @@ impl AppMetrics {
             .fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn record_redactions(&self, count: u64) {
+        self.total_redactions.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Records one Whisper job: time spent queued, audio length, and inference time.
+    pub fn record_transcription_job(&self, guild_id: GuildId, model: &str, timing: JobTiming) {
+        self.queue_wait.observe(timing.queue_wait.as_secs_f64());
+        self.chunk_length.observe(timing.audio_length.as_secs_f64());
+        self.transcription_latency
+            .observe(timing.inference.as_secs_f64());
+
+        let mut performance = self.performance.lock().unwrap();
+        match performance.get_mut(&(guild_id, model.to_string())) {
+            Some(series) => series.record(timing),
+            None => {
+                let mut series = PerformanceSeries::default();
+                series.record(timing);
+                performance.insert((guild_id, model.to_string()), series);
+            }
+        }
+    }
+
+    pub fn set_gateway_connected(&self, connected: bool) {
+        self.gateway_connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn gateway_connected(&self) -> bool {
+        self.gateway_connected.load(Ordering::Relaxed)
+    }
+
+    /// Marks the start of a Whisper job so probes can detect a hung worker.
+    pub fn record_job_started(&self) {
+        let now = self.start_time.elapsed().as_millis() as u64;
+        self.job_started_ms.store(now + 1, Ordering::Relaxed);
+    }
+
+    /// Marks the end of a Whisper job, whether or not it produced a caption.
+    pub fn record_job_finished(&self) {
+        let now = self.start_time.elapsed().as_millis() as u64;
+        self.job_started_ms.store(0, Ordering::Relaxed);
+        self.last_job_finished_ms.store(now, Ordering::Relaxed);
+    }
+
+    /// How long the current Whisper job has been running, if one is in flight.
+    pub fn job_in_flight_for(&self) -> Option<Duration> {
+        let started = self.job_started_ms.load(Ordering::Relaxed);
+        if started == 0 {
+            return None;
+        }
+        let now = self.start_time.elapsed().as_millis() as u64;
+        Some(Duration::from_millis(now.saturating_sub(started - 1)))
+    }
+
+    /// Time since the worker last finished a job (or since startup if it never has).
+    pub fn since_last_job_finished(&self) -> Duration {
+        let finished = self.last_job_finished_ms.load(Ordering::Relaxed);
+        let now = self.start_time.elapsed().as_millis() as u64;
+        Duration::from_millis(now.saturating_sub(finished))
+    }
+
+    pub fn transcription_latency(&self) -> HistogramSnapshot {
+        self.transcription_latency.snapshot()
+    }
+
+    pub fn chunk_length(&self) -> HistogramSnapshot {
+        self.chunk_length.snapshot()
+    }
+
+    pub fn queue_wait(&self) -> HistogramSnapshot {
+        self.queue_wait.snapshot()
+    }
+
+    pub fn last_transcription_epoch(&self) -> u64 {
+        self.last_transcription_epoch.load(Ordering::Relaxed)
+    }
+
+    pub fn record_retention_run(&self, run: RetentionRunSnapshot) {
+        *self.last_retention_run.lock().unwrap() = Some(run);
+    }
+
     pub fn snapshot(&self) -> MetricsSnapshot {
         let now = Instant::now();
         MetricsSnapshot {
@@ -95,11 +288,24 @@ impl AppMetrics {
             total_transcribed_lines: self.total_transcribed_lines.load(Ordering::Relaxed),
             total_sessions_started: self.total_sessions_started.load(Ordering::Relaxed),
             total_sessions_completed: self.total_sessions_completed.load(Ordering::Relaxed),
+            total_redactions: self.total_redactions.load(Ordering::Relaxed),
             last_transcription_at: self.last_transcription_iso8601(),
             line_windows: self.line_window_snapshot(now),
+            last_retention_run: self.last_retention_run.lock().unwrap().clone(),
+            transcription_performance: self.performance_snapshot(),
         }
     }
 
+    fn performance_snapshot(&self) -> Vec<TranscriptionPerformance> {
+        let performance = self.performance.lock().unwrap();
+        let mut series: Vec<_> = performance
+            .iter()
+            .map(|((guild_id, model), series)| series.snapshot(*guild_id, model))
+            .collect();
+        series.sort_by(|a, b| a.guild_id.cmp(&b.guild_id).then(a.model.cmp(&b.model)));
+        series
+    }
+
     fn line_window_snapshot(&self, now: Instant) -> LineWindowSnapshot {
         LineWindowSnapshot {
             last_1h: self.window_1h.count(now),
@@ -163,3 +369,105 @@ impl LineWindow {
         }
     }
 }
+
+#[derive(Default)]
+struct PerformanceSeries {
+    jobs: u64,
+    audio_seconds: f64,
+    queue_wait_seconds: f64,
+    inference_seconds: f64,
+    real_time_factor_sum: f64,
+    recent: VecDeque<JobTiming>,
+}
+
+impl PerformanceSeries {
+    fn record(&mut self, timing: JobTiming) {
+        self.jobs += 1;
+        self.audio_seconds += timing.audio_length.as_secs_f64();
+        self.queue_wait_seconds += timing.queue_wait.as_secs_f64();
+        self.inference_seconds += timing.inference.as_secs_f64();
+        self.real_time_factor_sum += timing.real_time_factor();
+        if self.recent.len() == PERFORMANCE_SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(timing);
+    }
+
+    fn snapshot(&self, guild_id: GuildId, model: &str) -> TranscriptionPerformance {
+        let collect = |value: fn(&JobTiming) -> f64| -> Percentiles {
+            Percentiles::from_samples(self.recent.iter().map(value).collect())
+        };
+        TranscriptionPerformance {
+            guild_id: guild_id.to_string(),
+            model: model.to_string(),
+            jobs: self.jobs,
+            audio_seconds_total: self.audio_seconds,
+            queue_wait_seconds_total: self.queue_wait_seconds,
+            inference_seconds_total: self.inference_seconds,
+            real_time_factor: if self.audio_seconds > 0.0 {
+                self.inference_seconds / self.audio_seconds
+            } else {
+                0.0
+            },
+            real_time_factor_sum: self.real_time_factor_sum,
+            audio_seconds: collect(|timing| timing.audio_length.as_secs_f64()),
+            queue_wait_seconds: collect(|timing| timing.queue_wait.as_secs_f64()),
+            inference_seconds: collect(|timing| timing.inference.as_secs_f64()),
+            real_time_factor_percentiles: collect(JobTiming::real_time_factor),
+        }
+    }
+}
+
+/// Cumulative histogram with fixed bucket bounds, rendered in Prometheus form.
+struct Histogram {
+    bounds: &'static [f64],
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+#[derive(Clone, Debug)]
+pub struct HistogramSnapshot {
+    /// `(upper_bound, cumulative_count)` pairs, excluding `+Inf`.
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: bounds.iter().map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, value: f64) {
+        if let Some(idx) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add((value.max(0.0) * 1_000_000.0) as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .bounds
+            .iter()
+            .zip(&self.buckets)
+            .map(|(bound, bucket)| {
+                cumulative += bucket.load(Ordering::Relaxed);
+                (*bound, cumulative)
+            })
+            .collect();
+        HistogramSnapshot {
+            buckets,
+            count: self.count.load(Ordering::Relaxed),
+            sum: self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        }
+    }
+}
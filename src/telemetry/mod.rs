@@ -1,4 +1,6 @@
+mod admin;
 pub mod metrics;
+pub mod prometheus;
 pub mod server;
 
 pub use metrics::AppMetrics;
@@ -0,0 +1,259 @@
+use std::fmt::Write;
+
+use super::{
+    AppMetrics,
+    metrics::{HistogramSnapshot, Percentiles, TranscriptionPerformance},
+};
+
+/// Point-in-time bot state that lives outside `AppMetrics`.
+pub struct ConnectionGauges {
+    pub connected_guilds: usize,
+    pub connected_channels: usize,
+    pub active_participants: usize,
+}
+
+/// Renders metrics in the Prometheus text exposition format (version 0.0.4).
+pub fn render(metrics: &AppMetrics, gauges: &ConnectionGauges) -> String {
+    let snapshot = metrics.snapshot();
+    let mut out = String::with_capacity(4096);
+
+    write_scalar(
+        &mut out,
+        "hammock_uptime_seconds",
+        "gauge",
+        "Seconds since the process started.",
+        snapshot.uptime_seconds as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_transcribed_lines_total",
+        "counter",
+        "Caption lines written by the transcription worker.",
+        snapshot.total_transcribed_lines as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_sessions_started_total",
+        "counter",
+        "Caption sessions started via /join.",
+        snapshot.total_sessions_started as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_sessions_completed_total",
+        "counter",
+        "Caption sessions finished via /leave.",
+        snapshot.total_sessions_completed as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_redactions_total",
+        "counter",
+        "PII matches redacted from caption lines.",
+        snapshot.total_redactions as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_last_transcription_timestamp_seconds",
+        "gauge",
+        "Unix time of the most recent caption line (0 if none yet).",
+        metrics.last_transcription_epoch() as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_connected_guilds",
+        "gauge",
+        "Guilds with an active voice connection.",
+        gauges.connected_guilds as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_connected_channels",
+        "gauge",
+        "Voice channels currently being captioned.",
+        gauges.connected_channels as f64,
+    );
+    write_scalar(
+        &mut out,
+        "hammock_active_participants",
+        "gauge",
+        "Non-bot members in captioned voice channels.",
+        gauges.active_participants as f64,
+    );
+
+    write_histogram(
+        &mut out,
+        "hammock_transcription_latency_seconds",
+        "Whisper inference time per audio chunk.",
+        &metrics.transcription_latency(),
+    );
+    write_histogram(
+        &mut out,
+        "hammock_audio_chunk_length_seconds",
+        "Length of audio chunks submitted for transcription.",
+        &metrics.chunk_length(),
+    );
+    write_histogram(
+        &mut out,
+        "hammock_transcription_queue_wait_seconds",
+        "Time audio chunks spend queued before Whisper picks them up.",
+        &metrics.queue_wait(),
+    );
+
+    write_performance(&mut out, &snapshot.transcription_performance);
+
+    out
+}
+
+/// Per guild/model summaries over the recent job window, plus lifetime totals.
+fn write_performance(out: &mut String, series: &[TranscriptionPerformance]) {
+    if series.is_empty() {
+        return;
+    }
+    type Extract = fn(&TranscriptionPerformance) -> (&Percentiles, f64);
+    let summaries: [(&str, &str, Extract); 4] = [
+        (
+            "hammock_whisper_audio_seconds",
+            "Audio length per Whisper job by guild and model.",
+            |perf| (&perf.audio_seconds, perf.audio_seconds_total),
+        ),
+        (
+            "hammock_whisper_queue_wait_seconds",
+            "Queue wait per Whisper job by guild and model.",
+            |perf| (&perf.queue_wait_seconds, perf.queue_wait_seconds_total),
+        ),
+        (
+            "hammock_whisper_inference_seconds",
+            "Inference time per Whisper job by guild and model.",
+            |perf| (&perf.inference_seconds, perf.inference_seconds_total),
+        ),
+        (
+            "hammock_whisper_job_real_time_factor",
+            "Inference time divided by audio length per Whisper job by guild and model.",
+            |perf| {
+                (
+                    &perf.real_time_factor_percentiles,
+                    perf.real_time_factor_sum,
+                )
+            },
+        ),
+    ];
+    for (name, help, extract) in summaries {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} summary");
+        for perf in series {
+            let labels = series_labels(perf);
+            let (percentiles, total) = extract(perf);
+            write_quantiles(out, name, &labels, percentiles);
+            let _ = writeln!(out, "{name}_sum{{{labels}}} {total}");
+            let _ = writeln!(out, "{name}_count{{{labels}}} {}", perf.jobs);
+        }
+    }
+
+    let name = "hammock_whisper_real_time_factor";
+    let _ = writeln!(
+        out,
+        "# HELP {name} Total inference time divided by total audio length (above 1 means Whisper is falling behind)."
+    );
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    for perf in series {
+        let labels = series_labels(perf);
+        let _ = writeln!(out, "{name}{{{labels}}} {}", perf.real_time_factor);
+    }
+}
+
+fn write_quantiles(out: &mut String, name: &str, labels: &str, percentiles: &Percentiles) {
+    for (quantile, value) in [
+        ("0.5", percentiles.p50),
+        ("0.9", percentiles.p90),
+        ("0.99", percentiles.p99),
+    ] {
+        let _ = writeln!(out, "{name}{{{labels},quantile=\"{quantile}\"}} {value}");
+    }
+}
+
+fn series_labels(perf: &TranscriptionPerformance) -> String {
+    format!(
+        "guild_id=\"{}\",model=\"{}\"",
+        perf.guild_id,
+        escape_label(&perf.model)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_scalar(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn write_histogram(out: &mut String, name: &str, help: &str, histogram: &HistogramSnapshot) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    for (bound, count) in &histogram.buckets {
+        let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}");
+    }
+    let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", histogram.count);
+    let _ = writeln!(out, "{name}_sum {}", histogram.sum);
+    let _ = writeln!(out, "{name}_count {}", histogram.count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn performance() -> TranscriptionPerformance {
+        let percentiles = |p50, p90, p99| Percentiles {
+            p50,
+            p90,
+            p99,
+            max: p99,
+        };
+        TranscriptionPerformance {
+            guild_id: "1".to_string(),
+            model: "base.en".to_string(),
+            jobs: 2,
+            audio_seconds_total: 10.0,
+            queue_wait_seconds_total: 1.0,
+            inference_seconds_total: 5.0,
+            real_time_factor: 0.5,
+            real_time_factor_sum: 1.2,
+            audio_seconds: percentiles(5.0, 5.0, 5.0),
+            queue_wait_seconds: percentiles(0.5, 0.5, 0.5),
+            inference_seconds: percentiles(2.5, 2.5, 2.5),
+            real_time_factor_percentiles: percentiles(0.4, 0.8, 0.8),
+        }
+    }
+
+    #[test]
+    fn real_time_factor_splits_into_summary_and_gauge() {
+        let mut out = String::new();
+        write_performance(&mut out, &[performance()]);
+        let labels = r#"guild_id="1",model="base.en""#;
+
+        assert!(out.contains("# TYPE hammock_whisper_job_real_time_factor summary\n"));
+        assert!(out.contains(&format!(
+            "hammock_whisper_job_real_time_factor{{{labels},quantile=\"0.9\"}} 0.8\n"
+        )));
+        assert!(out.contains(&format!(
+            "hammock_whisper_job_real_time_factor_sum{{{labels}}} 1.2\n"
+        )));
+        assert!(out.contains(&format!(
+            "hammock_whisper_job_real_time_factor_count{{{labels}}} 2\n"
+        )));
+
+        assert!(out.contains("# TYPE hammock_whisper_real_time_factor gauge\n"));
+        assert!(out.contains(&format!(
+            "hammock_whisper_real_time_factor{{{labels}}} 0.5\n"
+        )));
+        assert!(!out.contains(
+            "hammock_whisper_real_time_factor{guild_id=\"1\",model=\"base.en\",quantile"
+        ));
+    }
+}
@@ -1,16 +1,40 @@
 use std::{
+    borrow::Cow,
     net::SocketAddr,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
-use actix_web::{App, HttpResponse, HttpServer, Responder, http::header, web};
+use actix_web::{
+    App, HttpRequest, HttpResponse, HttpResponseBuilder, HttpServer, Responder, http::header, web,
+};
 use anyhow::Result;
-use serde::Serialize;
-use tokio::task::JoinHandle;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use tokio::{
+    sync::broadcast,
+    task::JoinHandle,
+    time::{Interval, MissedTickBehavior},
+};
+use utoipa::{
+    IntoParams, Modify, OpenApi, ToSchema,
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
 
-use crate::{BotState, telemetry::metrics::MetricsSnapshot};
+use crate::{
+    BotState,
+    captions::{
+        CaptionEvent,
+        index::{SearchHit, SearchQuery, parse_date_bound},
+    },
+    redaction::Redactor,
+    telemetry::{
+        metrics::MetricsSnapshot,
+        prometheus::{self, ConnectionGauges},
+    },
+};
 
-use super::AppMetrics;
+use super::{AppMetrics, admin};
 
 #[derive(Clone, Default)]
 pub struct InviteTracker {
@@ -29,10 +53,56 @@ impl InviteTracker {
 }
 
 #[derive(Clone)]
-struct HttpAppState {
-    bot_state: Arc<BotState>,
+pub(super) struct HttpAppState {
+    pub(super) bot_state: Arc<BotState>,
     metrics: Arc<AppMetrics>,
     invite: InviteTracker,
+    api_token: Option<Arc<str>>,
+    stall_threshold: Duration,
+}
+
+impl HttpAppState {
+    /// Checks the `Authorization: Bearer <HTTP_API_TOKEN>` header. Endpoints that expose
+    /// transcript content stay disabled until a token is configured.
+    pub(super) fn authorize(&self, req: &HttpRequest) -> Result<(), HttpResponse> {
+        self.authorize_with_query(req, None)
+    }
+
+    /// Like [`Self::authorize`], but also accepts the token as a query parameter for
+    /// clients such as `EventSource` and OBS browser sources that cannot set headers.
+    fn authorize_with_query(
+        &self,
+        req: &HttpRequest,
+        query_token: Option<&str>,
+    ) -> Result<(), HttpResponse> {
+        let Some(expected) = self.api_token.as_deref() else {
+            return Err(HttpResponse::ServiceUnavailable()
+                .body("HTTP_API_TOKEN is not configured; transcript endpoints are disabled"));
+        };
+        let provided = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or(query_token)
+            .map(str::trim);
+        match provided {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(()),
+            _ => Err(HttpResponse::Unauthorized()
+                .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                .body("Missing or invalid bearer token")),
+        }
+    }
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    left.iter()
+        .zip(right)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
 }
 
 pub fn spawn_http_server(
@@ -40,11 +110,15 @@ pub fn spawn_http_server(
     bot_state: Arc<BotState>,
     metrics: Arc<AppMetrics>,
     invite: InviteTracker,
+    api_token: Option<String>,
+    stall_threshold: Duration,
 ) -> Result<JoinHandle<()>> {
     let server_state = HttpAppState {
         bot_state,
         metrics,
         invite,
+        api_token: api_token.map(Arc::from),
+        stall_threshold,
     };
 
     let server = HttpServer::new(move || {
@@ -53,8 +127,15 @@ pub fn spawn_http_server(
             .route("/k8s/readyz", web::get().to(handle_readyz))
             .route("/k8s/livez", web::get().to(handle_livez))
             .route("/k8s/metrics", web::get().to(handle_metrics))
+            .route("/metrics", web::get().to(handle_prometheus))
             .route("/invite", web::get().to(handle_invite))
+            .route("/api/search", web::get().to(handle_search))
+            .route("/api/live/{guild_id}", web::get().to(handle_live_feed))
+            .route("/overlay/{guild_id}", web::get().to(handle_overlay))
+            .configure(admin::configure)
             .route("/docs", web::get().to(swagger_docs))
+            .route("/docs/ui", web::get().to(swagger_ui))
+            .route("/docs/ui/{asset}", web::get().to(swagger_asset))
     })
     .bind(bind_addr)?
     .run();
@@ -66,41 +147,165 @@ pub fn spawn_http_server(
     }))
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+struct ProbeCheck {
+    name: &'static str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl ProbeCheck {
+    fn pass(name: &'static str) -> Self {
+        Self {
+            name,
+            ok: true,
+            detail: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+fn probe_status(checks: &[ProbeCheck]) -> (&'static str, HttpResponseBuilder) {
+    if checks.iter().all(|check| check.ok) {
+        ("ok", HttpResponse::Ok())
+    } else {
+        ("unavailable", HttpResponse::ServiceUnavailable())
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 struct ReadyResponse {
     status: &'static str,
     uptime_seconds: u64,
+    checks: Vec<ProbeCheck>,
 }
 
+/// Readiness probe
+///
+/// Checks the Discord gateway connection, the Whisper worker, and that the caption
+/// directory is writable.
+#[utoipa::path(
+    get,
+    path = "/k8s/readyz",
+    tag = "probes",
+    responses(
+        (status = 200, description = "Service is ready", body = ReadyResponse),
+        (status = 503, description = "One or more checks failed", body = ReadyResponse)
+    )
+)]
 async fn handle_readyz(state: web::Data<HttpAppState>) -> impl Responder {
     let snapshot = state.metrics.snapshot();
-    HttpResponse::Ok().json(ReadyResponse {
-        status: "ok",
+    let mut checks = Vec::with_capacity(3);
+
+    checks.push(if state.metrics.gateway_connected() {
+        ProbeCheck::pass("discord_gateway")
+    } else {
+        ProbeCheck::fail("discord_gateway", "not connected to the Discord gateway")
+    });
+
+    // The model is loaded before the worker starts, so a live worker implies a loaded model.
+    checks.push(if state.bot_state.transcriber().is_running() {
+        ProbeCheck::pass("whisper_model")
+    } else {
+        ProbeCheck::fail("whisper_model", "transcription worker has exited")
+    });
+
+    let store = state.bot_state.caption_store();
+    let writable = tokio::task::spawn_blocking(move || store.check_writable()).await;
+    checks.push(match writable {
+        Ok(Ok(())) => ProbeCheck::pass("caption_storage"),
+        Ok(Err(err)) => ProbeCheck::fail(
+            "caption_storage",
+            format!("caption storage is not writable: {err:#}"),
+        ),
+        Err(err) => ProbeCheck::fail("caption_storage", format!("probe task failed: {err}")),
+    });
+
+    let (status, mut response) = probe_status(&checks);
+    response.json(ReadyResponse {
+        status,
         uptime_seconds: snapshot.uptime_seconds,
+        checks,
     })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct LiveResponse {
     status: &'static str,
     connected_servers: usize,
     connected_channels: usize,
     active_participants: usize,
+    queued_jobs: usize,
     last_transcription_at: Option<String>,
+    checks: Vec<ProbeCheck>,
 }
 
+/// Liveness probe
+///
+/// Fails when the transcription worker has exited or has made no progress within
+/// `TRANSCRIPTION_STALL_SECS` while jobs are pending.
+#[utoipa::path(
+    get,
+    path = "/k8s/livez",
+    tag = "probes",
+    responses(
+        (status = 200, description = "Service is alive", body = LiveResponse),
+        (status = 503, description = "Transcription loop is dead or stalled", body = LiveResponse)
+    )
+)]
 async fn handle_livez(state: web::Data<HttpAppState>) -> impl Responder {
     let snapshot = state.metrics.snapshot();
-    HttpResponse::Ok().json(LiveResponse {
-        status: "ok",
+    let transcriber = state.bot_state.transcriber();
+    let queued_jobs = transcriber.queued_jobs();
+    let threshold = state.stall_threshold;
+    let mut checks = Vec::with_capacity(2);
+
+    checks.push(if transcriber.is_running() {
+        ProbeCheck::pass("transcription_worker")
+    } else {
+        ProbeCheck::fail("transcription_worker", "transcription worker has exited")
+    });
+
+    let stalled = match state.metrics.job_in_flight_for() {
+        Some(running) if running > threshold => Some(format!(
+            "current job has been running for {}s",
+            running.as_secs()
+        )),
+        Some(_) => None,
+        None if queued_jobs > 0 && state.metrics.since_last_job_finished() > threshold => {
+            Some(format!(
+                "{queued_jobs} jobs queued but none completed in the last {}s",
+                threshold.as_secs()
+            ))
+        }
+        None => None,
+    };
+    checks.push(match stalled {
+        Some(detail) => ProbeCheck::fail("transcription_progress", detail),
+        None => ProbeCheck::pass("transcription_progress"),
+    });
+
+    let (status, mut response) = probe_status(&checks);
+    response.json(LiveResponse {
+        status,
         connected_servers: state.bot_state.connected_guilds(),
         connected_channels: state.bot_state.connected_channels(),
         active_participants: state.bot_state.active_participants(),
+        queued_jobs,
         last_transcription_at: snapshot.last_transcription_at,
+        checks,
     })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct MetricsResponse {
     connected_servers: usize,
     connected_channels: usize,
@@ -108,6 +313,13 @@ struct MetricsResponse {
     metrics: MetricsSnapshot,
 }
 
+/// Structured metrics
+#[utoipa::path(
+    get,
+    path = "/k8s/metrics",
+    tag = "metrics",
+    responses((status = 200, description = "JSON metrics payload", body = MetricsResponse))
+)]
 async fn handle_metrics(state: web::Data<HttpAppState>) -> impl Responder {
     let snapshot = state.metrics.snapshot();
     HttpResponse::Ok().json(MetricsResponse {
@@ -118,6 +330,277 @@ async fn handle_metrics(state: web::Data<HttpAppState>) -> impl Responder {
     })
 }
 
+/// Prometheus metrics
+///
+/// Counters, connection gauges, and histograms for transcription latency, chunk length,
+/// and queue wait time, plus per guild/model Whisper summaries.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses((
+        status = 200,
+        description = "Prometheus text exposition format",
+        body = String,
+        content_type = "text/plain"
+    ))
+)]
+async fn handle_prometheus(state: web::Data<HttpAppState>) -> impl Responder {
+    let gauges = ConnectionGauges {
+        connected_guilds: state.bot_state.connected_guilds(),
+        connected_channels: state.bot_state.connected_channels(),
+        active_participants: state.bot_state.active_participants(),
+    };
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(prometheus::render(&state.metrics, &gauges))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SearchParams {
+    guild_id: u64,
+    q: String,
+    channel_id: Option<u64>,
+    speaker_id: Option<u64>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct SearchResponse {
+    query: String,
+    hits: Vec<SearchHit>,
+}
+
+/// Search caption sessions
+///
+/// Full-text search over stored caption lines for one guild.
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    tag = "sessions",
+    params(SearchParams),
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Matching caption lines", body = SearchResponse),
+        (status = 400, description = "Invalid guild id or date bound"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured")
+    )
+)]
+async fn handle_search(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    params: web::Query<SearchParams>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize(&req) {
+        return response;
+    }
+    let params = params.into_inner();
+    if params.guild_id == 0 {
+        return HttpResponse::BadRequest().body("guild_id must be a Discord snowflake");
+    }
+    let since = match params
+        .since
+        .as_deref()
+        .map(|raw| parse_date_bound(raw, false))
+    {
+        Some(None) => {
+            return HttpResponse::BadRequest().body("since must be RFC 3339 or YYYY-MM-DD");
+        }
+        other => other.flatten(),
+    };
+    let until = match params
+        .until
+        .as_deref()
+        .map(|raw| parse_date_bound(raw, true))
+    {
+        Some(None) => {
+            return HttpResponse::BadRequest().body("until must be RFC 3339 or YYYY-MM-DD");
+        }
+        other => other.flatten(),
+    };
+
+    let query = SearchQuery {
+        guild_id: Some(GuildId::new(params.guild_id)),
+        text: params.q.clone(),
+        channel_id: params.channel_id.filter(|id| *id > 0).map(ChannelId::new),
+        speaker_id: params.speaker_id.filter(|id| *id > 0).map(UserId::new),
+        since,
+        until,
+        limit: params.limit.unwrap_or(25).min(200),
+    };
+    let index = state.bot_state.session_index();
+    let result = tokio::task::spawn_blocking(move || {
+        index.refresh()?;
+        Ok::<_, anyhow::Error>(index.search(&query))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(hits)) => HttpResponse::Ok().json(SearchResponse {
+            query: params.q,
+            hits,
+        }),
+        Ok(Err(err)) => {
+            tracing::error!(?err, "Session index refresh failed");
+            HttpResponse::InternalServerError().body("Failed to refresh session index")
+        }
+        Err(err) => {
+            tracing::error!("search task join error: {err}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+const LIVE_KEEPALIVE: Duration = Duration::from_secs(15);
+const OVERLAY_HTML: &str = include_str!("overlay.html");
+const SWAGGER_UI_HTML: &str = include_str!("swagger.html");
+/// Swagger UI is vendored so `/docs/ui` works without reaching a CDN.
+const SWAGGER_UI_BUNDLE: &str = include_str!("../../vendor/swagger-ui/swagger-ui-bundle.js");
+const SWAGGER_UI_CSS: &str = include_str!("../../vendor/swagger-ui/swagger-ui.css");
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LiveParams {
+    channel_id: Option<u64>,
+    token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LiveCaption<'a> {
+    guild_id: String,
+    channel_id: String,
+    timestamp: &'a str,
+    speaker: &'a str,
+    speaker_id: Option<String>,
+    comment: Cow<'a, str>,
+}
+
+struct LiveStream {
+    events: broadcast::Receiver<CaptionEvent>,
+    keepalive: Interval,
+    guild_id: GuildId,
+    channel_id: Option<ChannelId>,
+    redactor: Arc<Redactor>,
+}
+
+impl LiveStream {
+    /// Waits for the next caption for this guild (and channel, if filtered) and renders
+    /// it as a Server-Sent Events frame. Returns `None` once the caption sink is gone.
+    async fn next_frame(&mut self) -> Option<String> {
+        loop {
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Ok(event) => {
+                        if event.guild_id != self.guild_id
+                            || self.channel_id.is_some_and(|channel| event.channel_id != channel)
+                        {
+                            continue;
+                        }
+                        let payload = LiveCaption {
+                            guild_id: event.guild_id.to_string(),
+                            channel_id: event.channel_id.to_string(),
+                            timestamp: &event.entry.timestamp,
+                            speaker: &event.entry.speaker.name,
+                            speaker_id: event.entry.speaker.id.map(|id| id.to_string()),
+                            comment: self.redactor.export_text(&event.entry.comment),
+                        };
+                        let data = serde_json::to_string(&payload).ok()?;
+                        return Some(format!("event: caption\ndata: {data}\n\n"));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        return Some(format!("event: lagged\ndata: {skipped}\n\n"));
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = self.keepalive.tick() => return Some(": keepalive\n\n".to_string()),
+            }
+        }
+    }
+}
+
+/// Live caption stream
+///
+/// Server-Sent Events stream of captions as they are written. Each `caption` event
+/// carries a JSON object with guild/channel ids, timestamp, speaker, and comment.
+/// Accepts the bearer header or the `token` query parameter.
+#[utoipa::path(
+    get,
+    path = "/api/live/{guild_id}",
+    tag = "live",
+    params(("guild_id" = String, Path, description = "Guild snowflake"), LiveParams),
+    security(("api_token" = [])),
+    responses(
+        (status = 200, description = "Stream of caption events", body = String, content_type = "text/event-stream"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 503, description = "HTTP_API_TOKEN is not configured")
+    )
+)]
+async fn handle_live_feed(
+    req: HttpRequest,
+    state: web::Data<HttpAppState>,
+    path: web::Path<u64>,
+    params: web::Query<LiveParams>,
+) -> HttpResponse {
+    if let Err(response) = state.authorize_with_query(&req, params.token.as_deref()) {
+        return response;
+    }
+    let guild_id = path.into_inner();
+    if guild_id == 0 {
+        return HttpResponse::BadRequest().body("guild_id must be a Discord snowflake");
+    }
+
+    let mut keepalive = tokio::time::interval(LIVE_KEEPALIVE);
+    keepalive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let live = LiveStream {
+        events: state.bot_state.subscribe_captions(),
+        keepalive,
+        guild_id: GuildId::new(guild_id),
+        channel_id: params.channel_id.filter(|id| *id > 0).map(ChannelId::new),
+        redactor: state.bot_state.redactor(),
+    };
+    let stream = futures_util::stream::unfold(live, |mut live| async move {
+        let frame = live.next_frame().await?;
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(frame)), live))
+    });
+
+    HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream)
+}
+
+/// Caption overlay page
+///
+/// Transparent HTML page for OBS browser sources that renders `/api/live/{guild_id}`.
+/// Accepts `channel_id`, `token`, `lines`, `hold` (seconds), and `font_size` (pixels).
+#[utoipa::path(
+    get,
+    path = "/overlay/{guild_id}",
+    tag = "live",
+    params(("guild_id" = String, Path, description = "Guild snowflake")),
+    responses((status = 200, description = "HTML overlay", body = String, content_type = "text/html"))
+)]
+async fn handle_overlay() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(OVERLAY_HTML)
+}
+
+/// Redirect to Discord invite
+#[utoipa::path(
+    get,
+    path = "/invite",
+    tag = "discord",
+    responses(
+        (status = 307, description = "Redirect to the bot invite URL"),
+        (status = 503, description = "Invite link not available yet")
+    )
+)]
 async fn handle_invite(state: web::Data<HttpAppState>) -> impl Responder {
     if let Some(url) = state.invite.get() {
         HttpResponse::TemporaryRedirect()
@@ -128,69 +611,85 @@ async fn handle_invite(state: web::Data<HttpAppState>) -> impl Responder {
     }
 }
 
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Hammock Control Plane",
+        version = "1.0.0",
+        description = "Endpoints for readiness, liveness, metrics, the invite flow, transcript search, live captions, and session control."
+    ),
+    paths(
+        handle_readyz,
+        handle_livez,
+        handle_metrics,
+        handle_prometheus,
+        handle_invite,
+        handle_search,
+        handle_live_feed,
+        handle_overlay,
+        swagger_docs,
+        swagger_ui
+    ),
+    modifiers(&BearerAuth)
+)]
+struct ApiDoc;
+
+/// Registers the `HTTP_API_TOKEN` bearer scheme referenced by protected endpoints.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_token",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("Value of HTTP_API_TOKEN"))
+                    .build(),
+            ),
+        );
+    }
+}
+
+fn openapi_document() -> utoipa::openapi::OpenApi {
+    let mut document = ApiDoc::openapi();
+    document.merge(admin::AdminApi::openapi());
+    document
+}
+
+/// OpenAPI specification
+#[utoipa::path(
+    get,
+    path = "/docs",
+    tag = "docs",
+    responses((status = 200, description = "OpenAPI document", content_type = "application/json"))
+)]
 async fn swagger_docs() -> impl Responder {
-    HttpResponse::Ok().json(swagger_document())
-}
-
-fn swagger_document() -> serde_json::Value {
-    serde_json::json!({
-        "openapi": "3.0.0",
-        "info": {
-            "title": "Hammock Control Plane",
-            "version": "1.0.0",
-            "description": "Lightweight endpoints for readiness, liveness, metrics, and invite flow."
-        },
-        "paths": {
-            "/k8s/readyz": {
-                "get": {
-                    "summary": "Readiness probe",
-                    "responses": {
-                        "200": {
-                            "description": "Service is ready"
-                        }
-                    }
-                }
-            },
-            "/k8s/livez": {
-                "get": {
-                    "summary": "Liveness probe",
-                    "responses": {
-                        "200": {
-                            "description": "Service is alive"
-                        }
-                    }
-                }
-            },
-            "/k8s/metrics": {
-                "get": {
-                    "summary": "Structured metrics",
-                    "responses": {
-                        "200": {
-                            "description": "JSON metrics payload"
-                        }
-                    }
-                }
-            },
-            "/invite": {
-                "get": {
-                    "summary": "Redirect to Discord invite",
-                    "responses": {
-                        "307": {
-                            "description": "Redirect"
-                        }
-                    }
-                }
-            },
-            "/docs": {
-                "get": {
-                    "summary": "OpenAPI specification",
-                    "responses": {
-                        "200": {
-                            "description": "OpenAPI document"
-                        }
-                    }
-                }
-            }
-        }
-    })
+    HttpResponse::Ok().json(openapi_document())
+}
+
+/// Swagger UI for the OpenAPI document
+#[utoipa::path(
+    get,
+    path = "/docs/ui",
+    tag = "docs",
+    responses((status = 200, description = "Swagger UI page", body = String, content_type = "text/html"))
+)]
+async fn swagger_ui() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(SWAGGER_UI_HTML)
+}
+
+async fn swagger_asset(path: web::Path<String>) -> impl Responder {
+    let (content_type, body) = match path.as_str() {
+        "swagger-ui-bundle.js" => ("text/javascript; charset=utf-8", SWAGGER_UI_BUNDLE),
+        "swagger-ui.css" => ("text/css; charset=utf-8", SWAGGER_UI_CSS),
+        _ => return HttpResponse::NotFound().finish(),
+    };
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(("Cache-Control", "public, max-age=86400"))
+        .body(body)
 }
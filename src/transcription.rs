@@ -1,8 +1,9 @@
 use std::{
     ffi::CStr,
     os::raw::{c_char, c_void},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Once},
+    time::{Duration, Instant},
 };
 
 use anyhow::Context as _;
@@ -14,7 +15,8 @@ use whisper_rs_sys::{ggml_log_level, whisper_log_set};
 
 use crate::{
     captions::{CaptionEntry, CaptionSink, SpeakerInfo},
-    telemetry::AppMetrics,
+    redaction::Redactor,
+    telemetry::{AppMetrics, metrics::JobTiming},
 };
 use whisper_rs::WhisperContextParameters;
 
@@ -32,18 +34,36 @@ pub struct TranscriptionJob {
     pub started_at: DateTime<Utc>,
 }
 
+struct QueuedJob {
+    job: TranscriptionJob,
+    enqueued_at: Instant,
+}
+
 #[derive(Clone)]
 pub struct TranscriptionHandle {
-    tx: mpsc::Sender<TranscriptionJob>,
+    tx: mpsc::Sender<QueuedJob>,
 }
 
 impl TranscriptionHandle {
     pub async fn submit(&self, job: TranscriptionJob) -> anyhow::Result<()> {
         self.tx
-            .send(job)
+            .send(QueuedJob {
+                job,
+                enqueued_at: Instant::now(),
+            })
             .await
             .context("transcription queue dropped")
     }
+
+    /// False once the worker task has exited (for example after a panic).
+    pub fn is_running(&self) -> bool {
+        !self.tx.is_closed()
+    }
+
+    /// Jobs waiting in the queue, not counting the one being transcribed.
+    pub fn queued_jobs(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
 }
 
 pub fn spawn_worker(
@@ -52,9 +72,10 @@ pub fn spawn_worker(
     language: Option<String>,
     use_gpu: bool,
     gpu_device: i32,
+    redactor: Arc<Redactor>,
     metrics: Arc<AppMetrics>,
 ) -> anyhow::Result<TranscriptionHandle> {
-    let (tx, mut rx) = mpsc::channel::<TranscriptionJob>(32);
+    let (tx, mut rx) = mpsc::channel::<QueuedJob>(32);
     let model_path_str = model_path
         .to_str()
         .context("WHISPER_MODEL_PATH must be valid UTF-8")?
@@ -73,24 +94,35 @@ pub fn spawn_worker(
         ctx_params.gpu_device(gpu_device);
     }
     install_whisper_logger();
-    let ctx = Arc::new(
-        WhisperContext::new_with_params(&model_path_str, ctx_params)
+    let model = Arc::new(LoadedModel {
+        ctx: WhisperContext::new_with_params(&model_path_str, ctx_params)
             .context("loading Whisper model")?,
-    );
+        label: model_label(&model_path),
+    });
 
     tokio::spawn(async move {
         let metrics = Arc::clone(&metrics);
-        while let Some(job) = rx.recv().await {
-            let ctx = Arc::clone(&ctx);
+        while let Some(QueuedJob { job, enqueued_at }) = rx.recv().await {
+            let model = Arc::clone(&model);
             let sink = Arc::clone(&sink);
             let language = language.clone();
+            let redactor = Arc::clone(&redactor);
             let metrics = Arc::clone(&metrics);
             if let Err(err) = tokio::task::spawn_blocking(move || {
-                if let Err(inner) =
-                    transcribe_and_write(ctx, sink, job, language.as_deref(), metrics)
-                {
+                let queue_wait = enqueued_at.elapsed();
+                metrics.record_job_started();
+                if let Err(inner) = transcribe_and_write(
+                    &model,
+                    sink,
+                    job,
+                    language.as_deref(),
+                    &redactor,
+                    Arc::clone(&metrics),
+                    queue_wait,
+                ) {
                     tracing::error!("transcription failed: {inner:?}");
                 }
+                metrics.record_job_finished();
             })
             .await
             {
@@ -102,24 +134,59 @@ pub fn spawn_worker(
     Ok(TranscriptionHandle { tx })
 }
 
+/// Whisper context plus the label (model file stem) used for per-model metrics.
+struct LoadedModel {
+    ctx: WhisperContext,
+    label: String,
+}
+
+fn model_label(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
 fn transcribe_and_write(
-    ctx: Arc<WhisperContext>,
+    model: &LoadedModel,
     sink: Arc<CaptionSink>,
     job: TranscriptionJob,
     language: Option<&str>,
+    redactor: &Redactor,
     metrics: Arc<AppMetrics>,
+    queue_wait: Duration,
 ) -> anyhow::Result<()> {
     if job.pcm.is_empty() {
         return Ok(());
     }
 
+    let audio_length =
+        Duration::from_secs_f64(job.pcm.len() as f64 / job.sample_rate.max(1) as f64);
+
     let audio = prepare_audio(&job.pcm, job.sample_rate);
-    let mut state = ctx.create_state()?;
+    let mut state = model.ctx.create_state()?;
     let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
     params.set_language(language);
     params.set_translate(false);
 
+    let inference_started = Instant::now();
     state.full(params, &audio)?;
+    let timing = JobTiming {
+        queue_wait,
+        audio_length,
+        inference: inference_started.elapsed(),
+    };
+    metrics.record_transcription_job(job.guild_id, &model.label, timing);
+    tracing::debug!(
+        target = "transcription",
+        guild = %job.guild_id,
+        model = %model.label,
+        audio_ms = audio_length.as_millis() as u64,
+        queue_wait_ms = queue_wait.as_millis() as u64,
+        inference_ms = timing.inference.as_millis() as u64,
+        real_time_factor = timing.real_time_factor(),
+        "whisper job finished"
+    );
 
     let mut text = String::new();
     let segments = state.full_n_segments();
@@ -156,14 +223,19 @@ fn transcribe_and_write(
     );
 
     let timestamp = job.started_at.format("%Y-%m-%dT%H:%M:%S").to_string();
-    let entry = CaptionEntry {
+    let mut entry = CaptionEntry {
         speaker: SpeakerInfo {
             id: job.speaker_id,
             name: job.speaker_name.clone(),
         },
         comment: normalized,
         timestamp,
+        tags: Vec::new(),
     };
+    let matched = redactor.apply_on_write(&mut entry);
+    if matched > 0 {
+        metrics.record_redactions(matched as u64);
+    }
     sink.append_json(job.guild_id, job.channel_id, entry)?;
     metrics.record_transcription_line();
     Ok(())
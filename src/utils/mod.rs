@@ -1,3 +1,7 @@
 pub mod discord;
+#[cfg(test)]
+pub mod test_http;
+pub mod text;
 
 pub use discord::resolve_user_name;
+pub use text::{hex, truncate};
@@ -0,0 +1,529 @@
+use std::{fs::OpenOptions, io::Write, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode, header::CONTENT_TYPE};
+use serde::Serialize;
+use serde_json::{Value, json, value::RawValue};
+use serenity::model::id::{ChannelId, GuildId};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    captions::{SessionDocument, SessionSummary, crypto::CaptionKeyring},
+    summaries::SummaryOutput,
+    utils::hex,
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Tests run the retry loop without waiting out the real backoff.
+const INITIAL_BACKOFF: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(1)
+};
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookEvent {
+    SessionStarted,
+    SessionEnded,
+    SummaryReady,
+    /// Sent by `hammock send-test-webhook`; always delivered regardless of filters.
+    Ping,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 3] = [
+        WebhookEvent::SessionStarted,
+        WebhookEvent::SessionEnded,
+        WebhookEvent::SummaryReady,
+    ];
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().replace('_', ".").as_str() {
+            "session.started" | "session.start" | "start" => Some(Self::SessionStarted),
+            "session.ended" | "session.end" | "end" => Some(Self::SessionEnded),
+            "summary.ready" | "summary" => Some(Self::SummaryReady),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SessionStarted => "session.started",
+            Self::SessionEnded => "session.ended",
+            Self::SummaryReady => "summary.ready",
+            Self::Ping => "ping",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WebhookConfig {
+    pub endpoints: Vec<String>,
+    pub secret: Option<String>,
+    pub events: Vec<WebhookEvent>,
+    pub max_attempts: u32,
+    pub dead_letter_path: PathBuf,
+    /// Embed the finalized session document in `session.ended`; otherwise send a link.
+    pub include_document: bool,
+    /// Base URL of the HTTP control plane, used for `document_url` links.
+    pub public_base_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    id: String,
+    event: &'static str,
+    occurred_at: String,
+    data: &'a Value,
+}
+
+#[derive(Serialize)]
+struct DeadLetter<'a> {
+    failed_at: String,
+    delivery_id: &'a str,
+    event: &'static str,
+    url: &'a str,
+    attempts: u32,
+    last_error: &'a str,
+    /// The event data, or its caption encryption envelope when a keyring is configured.
+    /// Kept raw so the envelope's field order, which `open_bytes` relies on, survives.
+    payload: Box<RawValue>,
+}
+
+/// Posts session lifecycle events to the configured endpoints. Each delivery is signed
+/// with `WEBHOOK_SECRET`, retried with exponential backoff, and appended to the
+/// dead-letter log once every attempt has failed.
+pub struct WebhookDispatcher {
+    config: Arc<WebhookConfig>,
+    client: Client,
+    /// Seals dead-letter payloads so failed deliveries stay encrypted at rest.
+    keyring: Option<Arc<CaptionKeyring>>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            client: Client::new(),
+            keyring: None,
+        }
+    }
+
+    pub fn with_keyring(mut self, keyring: Option<Arc<CaptionKeyring>>) -> Self {
+        self.keyring = keyring;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.config.endpoints.is_empty()
+    }
+
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.is_enabled() && (event == WebhookEvent::Ping || self.config.events.contains(&event))
+    }
+
+    /// Whether `session.ended` embeds the session document, so callers can skip loading it.
+    pub fn includes_document(&self) -> bool {
+        self.config.include_document
+    }
+
+    pub fn session_started(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        session_id: &str,
+        title: Option<&str>,
+    ) {
+        if !self.wants(WebhookEvent::SessionStarted) {
+            return;
+        }
+        self.emit(
+            WebhookEvent::SessionStarted,
+            json!({
+                "guild_id": guild_id.to_string(),
+                "channel_id": channel_id.to_string(),
+                "session_id": session_id,
+                "title": title,
+            }),
+        );
+    }
+
+    /// `document` should already have export redaction applied.
+    pub fn session_ended(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        session_id: &str,
+        summary: &SessionSummary,
+        document: Option<&SessionDocument>,
+    ) {
+        if !self.wants(WebhookEvent::SessionEnded) {
+            return;
+        }
+        let mut data = json!({
+            "guild_id": guild_id.to_string(),
+            "channel_id": channel_id.to_string(),
+            "session_id": session_id,
+            "title": summary.title,
+            "started_at": summary.started_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "duration_seconds": summary.duration.as_secs(),
+        });
+        if self.config.include_document
+            && let Some(document) = document
+        {
+            data["document"] = serde_json::to_value(document).unwrap_or(Value::Null);
+        }
+        if let Some(base) = self.config.public_base_url.as_deref() {
+            data["document_url"] = Value::String(format!(
+                "{}/api/sessions/{session_id}",
+                base.trim_end_matches('/')
+            ));
+        }
+        self.emit(WebhookEvent::SessionEnded, data);
+    }
+
+    pub fn summary_ready(
+        &self,
+        guild_id: GuildId,
+        session_id: &str,
+        title: Option<&str>,
+        summary: &SummaryOutput,
+    ) {
+        if !self.wants(WebhookEvent::SummaryReady) {
+            return;
+        }
+        self.emit(
+            WebhookEvent::SummaryReady,
+            json!({
+                "guild_id": guild_id.to_string(),
+                "session_id": session_id,
+                "title": title,
+                "summary": summary.to_markdown(),
+                "structured": summary.structured(),
+            }),
+        );
+    }
+
+    /// Sends a `ping` event to every endpoint and waits for the outcome. Returns the
+    /// number of endpoints that accepted it.
+    pub async fn ping(&self) -> usize {
+        let data = json!({ "message": "Hammock webhook test" });
+        let deliveries = self.config.endpoints.iter().map(|url| {
+            deliver(
+                self.client.clone(),
+                Arc::clone(&self.config),
+                self.keyring.clone(),
+                url.clone(),
+                WebhookEvent::Ping,
+                data.clone(),
+            )
+        });
+        futures_util::future::join_all(deliveries)
+            .await
+            .into_iter()
+            .filter(|delivered| *delivered)
+            .count()
+    }
+
+    fn emit(&self, event: WebhookEvent, data: Value) {
+        for url in &self.config.endpoints {
+            tokio::spawn(deliver(
+                self.client.clone(),
+                Arc::clone(&self.config),
+                self.keyring.clone(),
+                url.clone(),
+                event,
+                data.clone(),
+            ));
+        }
+    }
+}
+
+async fn deliver(
+    client: Client,
+    config: Arc<WebhookConfig>,
+    keyring: Option<Arc<CaptionKeyring>>,
+    url: String,
+    event: WebhookEvent,
+    data: Value,
+) -> bool {
+    let delivery_id = delivery_id();
+    let body = match serde_json::to_string(&Envelope {
+        id: delivery_id.clone(),
+        event: event.as_str(),
+        occurred_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        data: &data,
+    }) {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::error!(?err, event = event.as_str(), "Failed to serialize webhook");
+            return false;
+        }
+    };
+
+    let max_attempts = config.max_attempts.max(1);
+    let mut backoff = INITIAL_BACKOFF;
+    let mut last_error = String::new();
+    let mut attempts = 0;
+    while attempts < max_attempts {
+        attempts += 1;
+        let timestamp = Utc::now().timestamp().to_string();
+        let mut request = client
+            .post(&url)
+            .timeout(REQUEST_TIMEOUT)
+            .header(CONTENT_TYPE, "application/json")
+            .header("X-Hammock-Event", event.as_str())
+            .header("X-Hammock-Delivery", &delivery_id)
+            .header("X-Hammock-Timestamp", &timestamp)
+            .body(body.clone());
+        if let Some(secret) = config.secret.as_deref() {
+            request = request.header("X-Hammock-Signature", sign(secret, &timestamp, &body));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                tracing::debug!(%url, event = event.as_str(), attempts, "Webhook delivered");
+                return true;
+            }
+            Ok(response) => {
+                let status = response.status();
+                last_error = format!("HTTP {status}");
+                if !is_retryable(status) {
+                    break;
+                }
+            }
+            Err(err) => last_error = err.to_string(),
+        }
+
+        if attempts < max_attempts {
+            tracing::warn!(%url, event = event.as_str(), attempts, error = %last_error, "Webhook delivery failed; retrying");
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    tracing::error!(%url, event = event.as_str(), attempts, error = %last_error, "Webhook delivery abandoned");
+    let path = config.dead_letter_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let payload = match keyring.as_deref() {
+            Some(keyring) => seal_payload(keyring, &data)?,
+            None => RawValue::from_string(data.to_string())?,
+        };
+        let line = serde_json::to_string(&DeadLetter {
+            failed_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            delivery_id: &delivery_id,
+            event: event.as_str(),
+            url: &url,
+            attempts,
+            last_error: &last_error,
+            payload,
+        })?;
+        append_dead_letter(&path, &line)
+    })
+    .await;
+    if let Ok(Err(err)) | Err(err) = result.map_err(anyhow::Error::from) {
+        tracing::error!(?err, "Failed to write webhook dead letter");
+    }
+    false
+}
+
+/// Client errors other than timeouts and rate limits will not succeed on retry.
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// `sha256=<hex>` HMAC over `<timestamp>.<body>`, so receivers can reject replays.
+fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", hex(&digest))
+}
+
+fn delivery_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Replaces the payload with its caption envelope; `open_bytes` recovers it.
+fn seal_payload(keyring: &CaptionKeyring, data: &Value) -> Result<Box<RawValue>> {
+    let sealed = keyring.seal(&serde_json::to_vec(data)?)?;
+    Ok(RawValue::from_string(String::from_utf8(sealed)?)?)
+}
+
+fn append_dead_letter(path: &PathBuf, line: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{captions::crypto, utils::test_http};
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        assert_eq!(
+            sign("whsec_test", "1700000000", r#"{"id":"abc","event":"ping"}"#),
+            "sha256=7b98928c2116ad8cd81714ba3cfb49de5f12af8b703334358fc76507a1bd400d"
+        );
+    }
+
+    #[test]
+    fn signature_covers_timestamp() {
+        let body = r#"{"id":"abc","event":"ping"}"#;
+        assert_ne!(
+            sign("whsec_test", "1700000000", body),
+            sign("whsec_test", "1700000001", body)
+        );
+    }
+
+    fn test_config(max_attempts: u32) -> Arc<WebhookConfig> {
+        Arc::new(WebhookConfig {
+            endpoints: Vec::new(),
+            secret: Some("whsec_test".to_string()),
+            events: WebhookEvent::ALL.to_vec(),
+            max_attempts,
+            dead_letter_path: std::env::temp_dir()
+                .join(format!("hammock-webhooks-{}", delivery_id()))
+                .join("dead-letters.jsonl"),
+            include_document: false,
+            public_base_url: None,
+        })
+    }
+
+    /// Reads and removes the dead-letter log written under `config`.
+    fn take_dead_letters(config: &WebhookConfig) -> Vec<String> {
+        let contents = std::fs::read_to_string(&config.dead_letter_path).unwrap_or_default();
+        let _ = std::fs::remove_dir_all(config.dead_letter_path.parent().unwrap());
+        contents.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn retries_only_transient_statuses() {
+        assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::REQUEST_TIMEOUT));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_until_delivered() {
+        let (base_url, mut requests) =
+            test_http::serve(vec![(500, String::new()), (200, String::new())]).await;
+        let config = test_config(3);
+        let data = json!({ "session_id": "abc" });
+        let delivered = deliver(
+            Client::new(),
+            Arc::clone(&config),
+            None,
+            format!("{base_url}/hook"),
+            WebhookEvent::SessionEnded,
+            data,
+        )
+        .await;
+        assert!(delivered);
+
+        let first = requests.recv().await.unwrap();
+        let second = requests.recv().await.unwrap();
+        assert!(requests.try_recv().is_err());
+        assert_eq!(first.path, "/hook");
+        assert_eq!(first.header("x-hammock-event"), Some("session.ended"));
+        assert_eq!(
+            first.header("x-hammock-delivery"),
+            second.header("x-hammock-delivery")
+        );
+        let timestamp = second.header("x-hammock-timestamp").unwrap();
+        let body = String::from_utf8(second.body.clone()).unwrap();
+        assert_eq!(
+            second.header("x-hammock-signature"),
+            Some(sign("whsec_test", timestamp, &body).as_str())
+        );
+        assert!(take_dead_letters(&config).is_empty());
+    }
+
+    #[tokio::test]
+    async fn dead_letters_after_the_last_attempt() {
+        let (base_url, mut requests) = test_http::serve(vec![(503, String::new()); 3]).await;
+        let config = test_config(3);
+        let data = json!({ "session_id": "abc" });
+        let delivered = deliver(
+            Client::new(),
+            Arc::clone(&config),
+            None,
+            base_url.clone(),
+            WebhookEvent::SessionStarted,
+            data.clone(),
+        )
+        .await;
+        assert!(!delivered);
+
+        let mut sent = 0;
+        while requests.try_recv().is_ok() {
+            sent += 1;
+        }
+        assert_eq!(sent, 3);
+        let letters = take_dead_letters(&config);
+        assert_eq!(letters.len(), 1);
+        let letter: Value = serde_json::from_str(&letters[0]).unwrap();
+        assert_eq!(letter["event"], "session.started");
+        assert_eq!(letter["url"], base_url);
+        assert_eq!(letter["attempts"], 3);
+        assert!(letter["last_error"].as_str().unwrap().contains("503"));
+        assert_eq!(letter["payload"], data);
+    }
+
+    #[tokio::test]
+    async fn seals_dead_letters_and_stops_on_client_errors() {
+        let (base_url, mut requests) = test_http::serve(vec![(400, String::new())]).await;
+        let config = test_config(3);
+        let keyring = Arc::new(CaptionKeyring::from_key(&"11".repeat(32)).unwrap());
+        let data = json!({ "session_id": "abc", "document": { "transcriptions": [] } });
+        let delivered = deliver(
+            Client::new(),
+            Arc::clone(&config),
+            Some(Arc::clone(&keyring)),
+            base_url,
+            WebhookEvent::SessionEnded,
+            data.clone(),
+        )
+        .await;
+        assert!(!delivered);
+        assert!(requests.recv().await.is_some());
+        assert!(requests.try_recv().is_err());
+
+        let letters = take_dead_letters(&config);
+        assert_eq!(letters.len(), 1);
+        let letter: Value = serde_json::from_str(&letters[0]).unwrap();
+        assert_eq!(letter["attempts"], 1);
+        assert!(letter["last_error"].as_str().unwrap().contains("400"));
+        // Operators recover the payload by passing its raw JSON to `open_bytes`.
+        #[derive(serde::Deserialize)]
+        struct RawLetter {
+            payload: Box<RawValue>,
+        }
+        let raw: RawLetter = serde_json::from_str(&letters[0]).unwrap();
+        let sealed = raw.payload.get().as_bytes().to_vec();
+        let opened = crypto::open_bytes(Some(&keyring), sealed).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&opened).unwrap(), data);
+    }
+}